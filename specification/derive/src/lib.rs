@@ -0,0 +1,161 @@
+// `#[derive(Specification)]`, building the specification of a struct from its field attributes.
+//
+// Written against `proc_macro` alone, the input is walked token by token,
+// which is enough for plain structs with named fields.
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+/// Derives `fn specification() -> SpecificationCompositions<Self>`, the conjunction of
+/// a comparison for every `#[spec(..)]` setting on the fields of a struct:
+///
+/// - `min = value` is satisfied by field values greater than or equal to `value`,
+/// - `max = value` by field values less than or equal to `value`,
+/// - `eq = value` by field values equal to `value`.
+///
+/// Every comparison is a leaf named after its field, a struct without any is always satisfied.
+#[proc_macro_derive(Specification, attributes(spec))]
+pub fn derive_specification(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(output) => output,
+        Err(message) => format!("compile_error!({message:?});").parse().unwrap(),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream, String> {
+    let mut tokens = input.into_iter();
+    let mut name = None;
+    while let Some(token) = tokens.next() {
+        if let TokenTree::Ident(ident) = token {
+            match ident.to_string().as_str() {
+                "struct" => {
+                    name = tokens.next().map(|name| name.to_string());
+                    break;
+                }
+                "enum" | "union" => break,
+                _ => {}
+            }
+        }
+    }
+    let name = name.ok_or("Specification can only be derived for structs")?;
+    let fields = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group.stream(),
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err("Specification can't be derived for generic structs".to_string())
+        }
+        _ => {
+            return Err(
+                "Specification can only be derived for structs with named fields".to_string(),
+            )
+        }
+    };
+
+    let mut leaves = Vec::new();
+    for field in split_at_commas(fields) {
+        let mut comparisons = Vec::new();
+        let mut field_name = None;
+        let mut tokens = field.into_iter();
+        while let Some(token) = tokens.next() {
+            match token {
+                TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                    if let Some(TokenTree::Group(attribute)) = tokens.next() {
+                        comparisons.extend(parse_attribute(attribute.stream())?);
+                    }
+                }
+                TokenTree::Ident(ident) if ident.to_string() != "pub" => {
+                    field_name = Some(ident.to_string());
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let Some(field_name) = field_name else {
+            continue;
+        };
+        for comparison in comparisons {
+            leaves.push(format!(
+                "::specification::adapters::field({field_name:?}, |candidate: &Self| &candidate.{field_name}, {comparison})"
+            ));
+        }
+    }
+
+    let mut leaves = leaves.into_iter();
+    let body = match leaves.next() {
+        Some(first) => leaves.fold(
+            format!("::specification::Specification::<Self>::composite({first})"),
+            |composition, leaf| format!("{composition}.and({leaf})"),
+        ),
+        None => "::specification::SpecificationCompositions::True".to_string(),
+    };
+    format!(
+        "impl {name} {{
+            pub fn specification() -> ::specification::SpecificationCompositions<Self> {{
+                {body}
+            }}
+        }}"
+    )
+    .parse()
+    .map_err(|error| format!("{error:?}"))
+}
+
+// The comparisons of a `#[spec(..)]` attribute, none for any other attribute.
+fn parse_attribute(attribute: TokenStream) -> Result<Vec<String>, String> {
+    let mut tokens = attribute.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(settings)))
+            if ident.to_string() == "spec" && settings.delimiter() == Delimiter::Parenthesis =>
+        {
+            split_at_commas(settings.stream())
+                .into_iter()
+                .map(parse_setting)
+                .collect()
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+// A `key = value` setting, turned into the comparison it stands for.
+fn parse_setting(setting: Vec<TokenTree>) -> Result<String, String> {
+    let mut tokens = setting.into_iter();
+    let key = tokens.next().map(|key| key.to_string()).unwrap_or_default();
+    match tokens.next() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => {}
+        _ => return Err(format!("expected `{key} = value` in #[spec(..)]")),
+    }
+    let value = tokens.collect::<TokenStream>().to_string();
+    match key.as_str() {
+        "min" => Ok(format!("::specification::comparison::ge({value})")),
+        "max" => Ok(format!("::specification::comparison::le({value})")),
+        "eq" => Ok(format!(
+            "::specification::comparison::one_of(::std::vec![{value}])"
+        )),
+        _ => Err(format!(
+            "unknown #[spec(..)] setting `{key}`, expected min, max or eq"
+        )),
+    }
+}
+
+// Splits at the commas outside of any brackets, including the angle brackets of generics.
+fn split_at_commas(tokens: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0;
+    let mut arrow = false;
+    for token in tokens {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                ',' if depth == 0 => {
+                    parts.push(Vec::new());
+                    continue;
+                }
+                '<' => depth += 1,
+                '>' if !arrow => depth -= 1,
+                _ => {}
+            }
+            arrow = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+        parts.last_mut().unwrap().push(token);
+    }
+    parts.retain(|part| !part.is_empty());
+    parts
+}
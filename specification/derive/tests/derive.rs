@@ -0,0 +1,104 @@
+use specification::adapters::field;
+use specification::comparison::{ge, le, one_of};
+use specification::{Specification, SpecificationCompositions};
+use specification_derive::Specification;
+
+#[derive(Debug, Specification)]
+struct JobCandidate {
+    #[allow(dead_code)]
+    name: String,
+    #[spec(min = 5.0)]
+    years_of_experience: f64,
+    #[spec(min = 5, max = 1_000)]
+    pub github_contributions: i64,
+    #[spec(max = 130_000)]
+    pub(crate) desired_salary: i64,
+    #[spec(eq = true)]
+    science_degree: bool,
+}
+
+#[derive(Debug, Specification)]
+struct Anyone {}
+
+fn candidate(
+    years_of_experience: f64,
+    github_contributions: i64,
+    desired_salary: i64,
+) -> JobCandidate {
+    JobCandidate {
+        name: "Test".to_string(),
+        years_of_experience,
+        github_contributions,
+        desired_salary,
+        science_degree: true,
+    }
+}
+
+fn hand_written() -> SpecificationCompositions<JobCandidate> {
+    field(
+        "years_of_experience",
+        |candidate: &JobCandidate| &candidate.years_of_experience,
+        ge(5.0),
+    )
+    .and(field(
+        "github_contributions",
+        |candidate: &JobCandidate| &candidate.github_contributions,
+        ge(5),
+    ))
+    .and(field(
+        "github_contributions",
+        |candidate: &JobCandidate| &candidate.github_contributions,
+        le(1_000),
+    ))
+    .and(field(
+        "desired_salary",
+        |candidate: &JobCandidate| &candidate.desired_salary,
+        le(130_000),
+    ))
+    .and(field(
+        "science_degree",
+        |candidate: &JobCandidate| &candidate.science_degree,
+        one_of(vec![true]),
+    ))
+}
+
+#[test]
+fn test_derive_matches_hand_written() {
+    let derived = JobCandidate::specification();
+    let hand_written = hand_written();
+    let candidates = [
+        candidate(5.0, 10, 100_000),
+        candidate(4.9, 10, 100_000),
+        candidate(12.0, 4, 100_000),
+        candidate(12.0, 1_001, 100_000),
+        candidate(12.0, 10, 130_001),
+        JobCandidate {
+            science_degree: false,
+            ..candidate(12.0, 10, 100_000)
+        },
+    ];
+
+    assert_eq!(derived.to_string(), hand_written.to_string());
+    for candidate in &candidates {
+        assert_eq!(
+            derived.is_satisfied_by(candidate),
+            hand_written.is_satisfied_by(candidate)
+        );
+    }
+    assert!(derived.is_satisfied_by(&candidates[0]));
+    assert!(!derived.is_satisfied_by(&candidates[1]));
+}
+
+#[test]
+fn test_derive_leaves_named_after_fields() {
+    let reminder = JobCandidate::specification()
+        .reminder_unsatisfied_by(&candidate(12.0, 10, 130_001))
+        .unwrap();
+
+    assert_eq!(reminder.name(), "desired_salary");
+}
+
+#[test]
+fn test_derive_without_settings() {
+    assert!(Anyone::specification().is_satisfied_by(&Anyone {}));
+}
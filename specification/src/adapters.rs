@@ -0,0 +1,521 @@
+// Specifications lifting other specifications, or plain predicates,
+// over differently shaped candidates, or over the candidates a guard lets through.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use crate::Specification;
+
+/// Satisfied by `Ok` values satisfying `inner`, never by an `Err`.
+pub fn on_ok<S>(inner: S) -> OnOk<S> {
+    OnOk { inner }
+}
+
+/// Satisfied by `Err` values matching `predicate`, never by an `Ok`.
+pub fn on_err<E, F: Fn(&E) -> bool>(predicate: F) -> OnErr<F> {
+    OnErr { predicate }
+}
+
+/// Satisfied by candidates whose value returned by `get` satisfies `inner`,
+/// named after the `name` of the field `get` reads.
+pub fn field<T, V, F: Fn(&T) -> &V, S>(name: &'static str, get: F, inner: S) -> Field<F, S> {
+    Field { name, get, inner }
+}
+
+/// Like [`field`] for optional values: satisfied by candidates whose value returned by `get`
+/// is present and satisfies `inner`, an absent value is rejected unless configured otherwise
+/// with [`OptionalField::when_missing`].
+pub fn optional_field<T, V, F: Fn(&T) -> Option<&V>, S>(
+    name: &'static str,
+    get: F,
+    inner: S,
+) -> OptionalField<F, S> {
+    OptionalField {
+        name,
+        get,
+        inner,
+        missing: MissingKey::Reject,
+    }
+}
+
+/// Satisfied by maps whose every value satisfies `inner`, always by an empty map.
+pub fn for_all_values<S>(inner: S) -> MapValues<S> {
+    MapValues { inner, all: true }
+}
+
+/// Satisfied by maps with at least one value satisfying `inner`, never by an empty map.
+pub fn for_any_value<S>(inner: S) -> MapValues<S> {
+    MapValues { inner, all: false }
+}
+
+/// Satisfied by candidates satisfying `inner`, which only runs for the candidates satisfying
+/// `guard`, the others are accepted unless configured otherwise with [`Guarded::otherwise`].
+///
+/// Unlike `guard.and(inner)`, a candidate failing the guard isn't rejected, the rule just
+/// doesn't apply to it: "if they applied, check the salary" rather than "applied and salary".
+pub fn guarded<G, S>(guard: G, inner: S) -> Guarded<G, S> {
+    Guarded {
+        guard,
+        inner,
+        otherwise: true,
+    }
+}
+
+/// A decision table: the result paired with the first guard the candidate satisfies,
+/// or for candidates matching none, `false` unless configured otherwise with
+/// [`FirstApplicable::otherwise`].
+///
+/// Guards of different types can be mixed by turning them into compositions first.
+pub fn first_applicable<G>(rules: Vec<(G, bool)>) -> FirstApplicable<G> {
+    FirstApplicable {
+        rules,
+        otherwise: false,
+    }
+}
+
+/// Satisfied by candidates satisfying `inner`, or for the candidates satisfying `condition`,
+/// by those not satisfying `inner`, for rules whose polarity depends on the context.
+pub fn invert_if<C, S>(condition: C, inner: S) -> InvertIf<C, S> {
+    InvertIf { condition, inner }
+}
+
+/// Satisfied by maps whose entry under `name` satisfies `inner`,
+/// a missing entry is rejected unless configured otherwise with [`Key::when_missing`].
+pub fn key<S>(name: impl Into<String>, inner: S) -> Key<S> {
+    Key {
+        name: name.into(),
+        inner,
+        missing: MissingKey::Reject,
+    }
+}
+
+#[derive(Debug)]
+pub struct OnOk<S> {
+    inner: S,
+}
+
+impl<V: Debug, E: Debug, S: Specification<V>> Specification<Result<V, E>> for OnOk<S> {
+    fn is_satisfied_by(&self, candidate: &Result<V, E>) -> bool {
+        match candidate {
+            Ok(value) => self.inner.is_satisfied_by(value),
+            Err(_) => false,
+        }
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.inner.eq_by_debug()
+    }
+}
+
+pub struct OnErr<F> {
+    predicate: F,
+}
+
+impl<F> Debug for OnErr<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnErr").finish_non_exhaustive()
+    }
+}
+
+impl<V: Debug, E: Debug, F: Fn(&E) -> bool> Specification<Result<V, E>> for OnErr<F> {
+    fn is_satisfied_by(&self, candidate: &Result<V, E>) -> bool {
+        match candidate {
+            Ok(_) => false,
+            Err(error) => (self.predicate)(error),
+        }
+    }
+}
+
+pub struct Field<F, S> {
+    name: &'static str,
+    get: F,
+    inner: S,
+}
+
+impl<F, S: Debug> Debug for Field<F, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Field")
+            .field("name", &self.name)
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug, V: Debug, F: Fn(&T) -> &V, S: Specification<V>> Specification<T> for Field<F, S> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.inner.is_satisfied_by((self.get)(candidate))
+    }
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct Guarded<G, S> {
+    guard: G,
+    inner: S,
+    otherwise: bool,
+}
+
+impl<G, S> Guarded<G, S> {
+    /// The result for the candidates failing the guard, `true` by default.
+    pub fn otherwise(mut self, otherwise: bool) -> Self {
+        self.otherwise = otherwise;
+        self
+    }
+}
+
+impl<T: Debug, G: Specification<T>, S: Specification<T>> Specification<T> for Guarded<G, S> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        if self.guard.is_satisfied_by(candidate) {
+            self.inner.is_satisfied_by(candidate)
+        } else {
+            self.otherwise
+        }
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.guard.eq_by_debug() && self.inner.eq_by_debug()
+    }
+}
+
+pub struct OptionalField<F, S> {
+    name: &'static str,
+    get: F,
+    inner: S,
+    missing: MissingKey,
+}
+
+impl<F, S> OptionalField<F, S> {
+    pub fn when_missing(mut self, missing: MissingKey) -> Self {
+        self.missing = missing;
+        self
+    }
+}
+
+impl<F, S: Debug> Debug for OptionalField<F, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OptionalField")
+            .field("name", &self.name)
+            .field("inner", &self.inner)
+            .field("missing", &self.missing)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug, V: Debug, F: Fn(&T) -> Option<&V>, S: Specification<V>> Specification<T>
+    for OptionalField<F, S>
+{
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        match (self.get)(candidate) {
+            Some(value) => self.inner.is_satisfied_by(value),
+            None => self.missing == MissingKey::Accept,
+        }
+    }
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct MapValues<S> {
+    inner: S,
+    all: bool,
+}
+
+impl<K: Debug, V: Debug, S: Specification<V>> Specification<HashMap<K, V>> for MapValues<S> {
+    fn is_satisfied_by(&self, candidate: &HashMap<K, V>) -> bool {
+        let mut values = candidate.values();
+        if self.all {
+            values.all(|value| self.inner.is_satisfied_by(value))
+        } else {
+            values.any(|value| self.inner.is_satisfied_by(value))
+        }
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.inner.eq_by_debug()
+    }
+}
+
+#[derive(Debug)]
+pub struct FirstApplicable<G> {
+    rules: Vec<(G, bool)>,
+    otherwise: bool,
+}
+
+impl<G> FirstApplicable<G> {
+    /// The result for the candidates matching no guard, `false` by default.
+    pub fn otherwise(mut self, otherwise: bool) -> Self {
+        self.otherwise = otherwise;
+        self
+    }
+}
+
+impl<T: Debug, G: Specification<T>> Specification<T> for FirstApplicable<G> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.rules
+            .iter()
+            .find(|(guard, _)| guard.is_satisfied_by(candidate))
+            .map_or(self.otherwise, |(_, result)| *result)
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.rules.iter().all(|(guard, _)| guard.eq_by_debug())
+    }
+}
+
+#[derive(Debug)]
+pub struct InvertIf<C, S> {
+    condition: C,
+    inner: S,
+}
+
+impl<T: Debug, C: Specification<T>, S: Specification<T>> Specification<T> for InvertIf<C, S> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.inner.is_satisfied_by(candidate) != self.condition.is_satisfied_by(candidate)
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.condition.eq_by_debug() && self.inner.eq_by_debug()
+    }
+}
+
+/// How [`Key`] treats a map without the entry, and [`OptionalField`] an absent value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingKey {
+    Accept,
+    Reject,
+}
+
+#[derive(Debug)]
+pub struct Key<S> {
+    name: String,
+    inner: S,
+    missing: MissingKey,
+}
+
+impl<S> Key<S> {
+    pub fn when_missing(mut self, missing: MissingKey) -> Self {
+        self.missing = missing;
+        self
+    }
+}
+
+impl<V: Debug, S: Specification<V>> Specification<HashMap<String, V>> for Key<S> {
+    fn is_satisfied_by(&self, candidate: &HashMap<String, V>) -> bool {
+        match candidate.get(&self.name) {
+            Some(value) => self.inner.is_satisfied_by(value),
+            None => self.missing == MissingKey::Accept,
+        }
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.inner.eq_by_debug()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_on_ok() {
+        let specification = on_ok(GreaterThan { value: 5 });
+
+        assert!(specification.is_satisfied_by(&Ok::<i32, String>(6)));
+        assert!(!specification.is_satisfied_by(&Ok::<i32, String>(3)));
+        assert!(!specification.is_satisfied_by(&Err::<i32, String>("boom".to_string())));
+    }
+
+    #[test]
+    fn test_on_err() {
+        let specification = on_err(|error: &String| error.contains("timeout"));
+
+        assert!(specification.is_satisfied_by(&Err::<i32, String>("timeout".to_string())));
+        assert!(!specification.is_satisfied_by(&Err::<i32, String>("boom".to_string())));
+        assert!(!specification.is_satisfied_by(&Ok::<i32, String>(6)));
+    }
+
+    #[test]
+    fn test_on_ok_or_on_err() {
+        let specification =
+            on_ok(GreaterThan { value: 5 }).or(on_err(|error: &String| error.contains("timeout")));
+
+        assert!(specification.is_satisfied_by(&Ok(6)));
+        assert!(specification.is_satisfied_by(&Err("timeout".to_string())));
+        assert!(!specification.is_satisfied_by(&Err("boom".to_string())));
+    }
+
+    #[test]
+    fn test_field() {
+        let specification = field("0", |pair: &(i32, i32)| &pair.0, GreaterThan { value: 5 })
+            .and(field("1", |pair: &(i32, i32)| &pair.1, Zero {}));
+
+        assert!(specification.is_satisfied_by(&(6, 0)));
+        assert!(!specification.is_satisfied_by(&(3, 0)));
+        assert!(!specification.is_satisfied_by(&(6, 1)));
+        assert_eq!(
+            specification
+                .reminder_unsatisfied_by(&(3, 0))
+                .unwrap()
+                .name(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_guarded_guard_satisfied() {
+        let (less_than, evaluations) = counting(LessThan { value: 10 });
+        let specification = guarded(GreaterThan { value: 5 }, less_than);
+
+        assert!(specification.is_satisfied_by(&7));
+        assert!(!specification.is_satisfied_by(&12));
+        assert_eq!(count(&evaluations), 2);
+    }
+
+    #[test]
+    fn test_guarded_guard_unsatisfied() {
+        let (less_than, evaluations) = counting(LessThan { value: 0 });
+        let specification = guarded(GreaterThan { value: 5 }, less_than);
+
+        assert!(specification.is_satisfied_by(&3));
+        assert!(!GreaterThan { value: 5 }
+            .and(LessThan { value: 0 })
+            .is_satisfied_by(&3));
+        assert!(!specification.otherwise(false).is_satisfied_by(&3));
+        assert_eq!(count(&evaluations), 0);
+    }
+
+    #[test]
+    fn test_first_applicable() {
+        let specification = first_applicable(vec![
+            (GreaterThan { value: 100 }.composite(), false),
+            (GreaterThan { value: 5 }.composite(), true),
+            (LessThan { value: 0 }.composite(), true),
+        ]);
+
+        assert!(!specification.is_satisfied_by(&200));
+        assert!(specification.is_satisfied_by(&50));
+        assert!(specification.is_satisfied_by(&-1));
+        assert!(!specification.is_satisfied_by(&3));
+        assert!(specification.otherwise(true).is_satisfied_by(&3));
+    }
+
+    #[test]
+    fn test_first_applicable_stops_at_the_first_match() {
+        let (specification, evaluations) = counting(less_than_10());
+        let table = first_applicable(vec![
+            (greater_than_5().composite(), true),
+            (specification.composite(), false),
+        ]);
+
+        assert!(table.is_satisfied_by(&6));
+        assert_eq!(count(&evaluations), 0);
+    }
+
+    #[test]
+    fn test_invert_if() {
+        // Below zero the rule flips: even numbers are rejected instead of odd ones.
+        let specification = invert_if(LessThan { value: 0 }, on_even());
+
+        assert!(specification.is_satisfied_by(&4));
+        assert!(!specification.is_satisfied_by(&3));
+        assert!(!specification.is_satisfied_by(&-4));
+        assert!(specification.is_satisfied_by(&-3));
+    }
+
+    fn on_even() -> impl Specification<i32> {
+        #[derive(Debug)]
+        struct Even;
+
+        impl Specification<i32> for Even {
+            fn is_satisfied_by(&self, candidate: &i32) -> bool {
+                candidate % 2 == 0
+            }
+        }
+
+        Even
+    }
+
+    fn record(entries: &[(&str, i32)]) -> HashMap<String, i32> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn test_key() {
+        let specification = key("age", GreaterThan { value: 17 }).and(key("children", Zero {}));
+
+        assert!(specification.is_satisfied_by(&record(&[("age", 18), ("children", 0)])));
+        assert!(!specification.is_satisfied_by(&record(&[("age", 16), ("children", 0)])));
+        assert!(!specification.is_satisfied_by(&record(&[("age", 18)])));
+    }
+
+    #[derive(Debug)]
+    struct Address {
+        floor: Option<i32>,
+    }
+
+    #[derive(Debug)]
+    struct Candidate {
+        address: Option<Address>,
+    }
+
+    fn floor(candidate: &Candidate) -> Option<&i32> {
+        candidate.address.as_ref()?.floor.as_ref()
+    }
+
+    #[test]
+    fn test_optional_field() {
+        let specification = optional_field("floor", floor, GreaterThan { value: 5 });
+        let on_floor = |floor| Candidate {
+            address: Some(Address { floor: Some(floor) }),
+        };
+
+        assert!(specification.is_satisfied_by(&on_floor(6)));
+        assert!(!specification.is_satisfied_by(&on_floor(3)));
+        assert_eq!(specification.name(), "floor");
+    }
+
+    #[test]
+    fn test_optional_field_when_missing() {
+        let without_floor = Candidate {
+            address: Some(Address { floor: None }),
+        };
+        let without_address = Candidate { address: None };
+
+        let rejecting = optional_field("floor", floor, GreaterThan { value: 5 });
+        assert!(!rejecting.is_satisfied_by(&without_floor));
+        assert!(!rejecting.is_satisfied_by(&without_address));
+
+        let accepting = rejecting.when_missing(MissingKey::Accept);
+        assert!(accepting.is_satisfied_by(&without_floor));
+        assert!(accepting.is_satisfied_by(&without_address));
+    }
+
+    #[test]
+    fn test_for_all_values() {
+        let specification = for_all_values(GreaterThan { value: 5 });
+
+        assert!(specification.is_satisfied_by(&record(&[("a", 6), ("b", 7)])));
+        assert!(!specification.is_satisfied_by(&record(&[("a", 6), ("b", 3)])));
+        assert!(!specification.is_satisfied_by(&record(&[("a", 2), ("b", 3)])));
+        assert!(specification.is_satisfied_by(&record(&[])));
+    }
+
+    #[test]
+    fn test_for_any_value() {
+        let specification = for_any_value(GreaterThan { value: 5 });
+
+        assert!(specification.is_satisfied_by(&record(&[("a", 6), ("b", 7)])));
+        assert!(specification.is_satisfied_by(&record(&[("a", 6), ("b", 3)])));
+        assert!(!specification.is_satisfied_by(&record(&[("a", 2), ("b", 3)])));
+        assert!(!specification.is_satisfied_by(&record(&[])));
+    }
+
+    #[test]
+    fn test_key_when_missing() {
+        let specification = key("children", Zero {}).when_missing(MissingKey::Accept);
+
+        assert!(specification.is_satisfied_by(&record(&[("age", 18)])));
+        assert!(!specification.is_satisfied_by(&record(&[("children", 2)])));
+    }
+}
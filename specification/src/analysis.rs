@@ -0,0 +1,885 @@
+// Inspecting and comparing composition trees, without evaluating them.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::{Specification, SpecificationCompositions};
+
+/// A single difference between two trees. Paths are child indexes from the root,
+/// in the old tree for removed and changed nodes, in the new tree for added ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffEntry {
+    Added {
+        path: Vec<usize>,
+        node: String,
+    },
+    Removed {
+        path: Vec<usize>,
+        node: String,
+    },
+    Changed {
+        path: Vec<usize>,
+        from: String,
+        to: String,
+    },
+}
+
+impl Display for DiffEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { path, node } => write!(f, "+ {:?} {}", path, node),
+            Self::Removed { path, node } => write!(f, "- {:?} {}", path, node),
+            Self::Changed { path, from, to } => write!(f, "~ {:?} {} -> {}", path, from, to),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecDiff {
+    pub entries: Vec<DiffEntry>,
+}
+
+impl SpecDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Display for SpecDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// The most leaves [`SpecificationCompositions::satisfying_assignments`] enumerates
+/// the assignments of, as there are two to the power of their number.
+pub const MAX_ASSIGNMENT_LEAVES: usize = 20;
+
+/// Why [`SpecificationCompositions::satisfying_assignments`] couldn't enumerate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssignmentError {
+    TooManyLeaves(usize),
+    /// A leaf of the tree, by name, isn't among the given leaves.
+    UnlistedLeaf(String),
+}
+
+impl Display for AssignmentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyLeaves(count) => write!(
+                f,
+                "{} leaves, at most {} can be enumerated",
+                count, MAX_ASSIGNMENT_LEAVES
+            ),
+            Self::UnlistedLeaf(name) => write!(f, "the leaf `{}` isn't listed", name),
+        }
+    }
+}
+
+impl std::error::Error for AssignmentError {}
+
+/// Identifies a node of a tree, numbered in pre-order from the root, see
+/// [`SpecificationCompositions::assign_ids`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A tree mirroring a composition, every node carrying its [`NodeId`].
+#[derive(Debug)]
+pub struct IdentifiedTree<T: std::fmt::Debug> {
+    pub id: NodeId,
+    /// The [`Specification::name`] of the node.
+    pub name: String,
+    /// The specification itself for leaves, `None` for the other nodes.
+    pub leaf: Option<Arc<dyn Specification<T>>>,
+    pub children: Vec<IdentifiedTree<T>>,
+}
+
+impl<T: std::fmt::Debug> IdentifiedTree<T> {
+    /// The ids of the node and its descendants, in pre-order.
+    pub fn ids(&self) -> Vec<NodeId> {
+        let mut ids = vec![self.id];
+        for child in &self.children {
+            ids.extend(child.ids());
+        }
+        ids
+    }
+
+    pub fn find(&self, id: NodeId) -> Option<&Self> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(id))
+    }
+}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Numbers every node in pre-order, so the same tree always gets the same ids
+    /// and nodes can be referred to across evaluations.
+    pub fn assign_ids(&self) -> IdentifiedTree<T> {
+        self.identified(&mut 0)
+    }
+
+    fn identified(&self, next: &mut usize) -> IdentifiedTree<T> {
+        let id = NodeId(*next);
+        *next += 1;
+        IdentifiedTree {
+            id,
+            name: self.name(),
+            leaf: match self {
+                Self::Specification(f) => Some(f.clone()),
+                _ => None,
+            },
+            children: self
+                .children()
+                .into_iter()
+                .map(|child| child.identified(next))
+                .collect(),
+        }
+    }
+
+    /// Describes what changed from `self` to `other`.
+    ///
+    /// The children of `And`, `Or` and `Xor` are matched regardless of their order;
+    /// unmatched children of the same kind are diffed recursively, the rest is reported
+    /// as removed or added.
+    pub fn diff(&self, other: &Self) -> SpecDiff {
+        let mut diff = SpecDiff::default();
+        self.diff_into(other, &mut vec![], &mut vec![], &mut diff.entries);
+        diff
+    }
+
+    /// The minimum number of leaf results that would need to flip for the whole specification
+    /// to be satisfied: the failing conjuncts of an `And`, the closest alternative of an `Or`.
+    /// Leaves under an `Invert` count when they'd need to flip from true to false.
+    /// `usize::MAX` means no flip of the leaves can satisfy it, e.g. for `False`.
+    ///
+    /// For `WeightedAtLeast` the heaviest children are picked first, which is an estimate.
+    pub fn distance_to_satisfy(&self, candidate: &T) -> usize {
+        self.distance_to(true, candidate)
+    }
+
+    fn distance_to(&self, wanted: bool, candidate: &T) -> usize {
+        match self {
+            Self::Specification(f) => usize::from(f.is_satisfied_by(candidate) != wanted),
+            Self::True => Self::unreachable_unless(wanted),
+            Self::False => Self::unreachable_unless(!wanted),
+            Self::Invert(specification) => specification.distance_to(!wanted, candidate),
+            Self::And(specifications) if wanted => {
+                Self::sum_distances(specifications, true, candidate)
+            }
+            Self::And(specifications) => Self::min_distance(specifications, false, candidate),
+            Self::Or(specifications) if wanted => {
+                Self::min_distance(specifications, true, candidate)
+            }
+            Self::Or(specifications) => Self::sum_distances(specifications, false, candidate),
+            Self::Xor(specifications) => {
+                if self.is_satisfied_by(candidate) == wanted {
+                    return 0;
+                }
+                specifications
+                    .iter()
+                    .map(|specification| {
+                        specification
+                            .distance_to(!specification.is_satisfied_by(candidate), candidate)
+                    })
+                    .min()
+                    .unwrap_or(usize::MAX)
+            }
+            Self::WeightedAtLeast(threshold, specifications) => {
+                if self.is_satisfied_by(candidate) == wanted {
+                    return 0;
+                }
+                let mut weight = Self::satisfied_weight(specifications, candidate);
+                // Flip the heaviest children, that didn't hold or did, until the threshold is crossed.
+                let mut flippable: Vec<&(f64, Self)> = specifications
+                    .iter()
+                    .filter(|(_, specification)| specification.is_satisfied_by(candidate) != wanted)
+                    .collect();
+                flippable.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+                let mut distance: usize = 0;
+                for (child_weight, specification) in flippable {
+                    distance =
+                        distance.saturating_add(specification.distance_to(wanted, candidate));
+                    if wanted {
+                        weight += child_weight;
+                        if weight >= *threshold {
+                            return distance;
+                        }
+                    } else {
+                        weight -= child_weight;
+                        if weight < *threshold {
+                            return distance;
+                        }
+                    }
+                }
+                usize::MAX
+            }
+            // Enough of the children have to flip, the cheapest ones to flip are picked.
+            Self::MinSatisfied(count, specifications) => {
+                let satisfied = Self::satisfied_count(specifications, candidate);
+                if wanted {
+                    Self::flip_cheapest(
+                        specifications,
+                        true,
+                        count.saturating_sub(satisfied),
+                        candidate,
+                    )
+                } else {
+                    Self::flip_cheapest(
+                        specifications,
+                        false,
+                        (satisfied + 1).saturating_sub(*count),
+                        candidate,
+                    )
+                }
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                let satisfied = Self::satisfied_count(specifications, candidate);
+                if wanted {
+                    Self::flip_cheapest(
+                        specifications,
+                        false,
+                        satisfied.saturating_sub(*count),
+                        candidate,
+                    )
+                } else {
+                    Self::flip_cheapest(
+                        specifications,
+                        true,
+                        (count + 1).saturating_sub(satisfied),
+                        candidate,
+                    )
+                }
+            }
+        }
+    }
+
+    // The distance of turning `flips` of the children that aren't `wanted` yet into `wanted`.
+    fn flip_cheapest(specifications: &[Self], wanted: bool, flips: usize, candidate: &T) -> usize {
+        let mut distances: Vec<usize> = specifications
+            .iter()
+            .filter(|specification| specification.is_satisfied_by(candidate) != wanted)
+            .map(|specification| specification.distance_to(wanted, candidate))
+            .collect();
+        if distances.len() < flips {
+            return usize::MAX;
+        }
+        distances.sort_unstable();
+        distances
+            .into_iter()
+            .take(flips)
+            .fold(0usize, usize::saturating_add)
+    }
+
+    const fn unreachable_unless(reachable: bool) -> usize {
+        if reachable {
+            0
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn sum_distances(specifications: &[Self], wanted: bool, candidate: &T) -> usize {
+        specifications
+            .iter()
+            .fold(0usize, |distance, specification| {
+                distance.saturating_add(specification.distance_to(wanted, candidate))
+            })
+    }
+
+    fn min_distance(specifications: &[Self], wanted: bool, candidate: &T) -> usize {
+        specifications
+            .iter()
+            .map(|specification| specification.distance_to(wanted, candidate))
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// The distinct names of the leaves, sorted, i.e. the atomic criteria the rule depends on.
+    pub fn leaf_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        self.for_each_leaf(&mut |leaf| {
+            names.insert(leaf.name());
+        });
+        names
+    }
+
+    /// The leaves every satisfying candidate has to satisfy, the non-negotiable criteria, each
+    /// once in pre-order. They are worked out from the structure: an `And` needs what any of
+    /// its children needs, an `Or` what all of its children need, and a `MinSatisfied` what is
+    /// needed by so many children that the others can't reach the count. An `Invert` or a
+    /// `Xor` needs none of its leaves, as each can be met with them failing, and neither does
+    /// a plain `False` branch, which nothing satisfies anyway.
+    pub fn critical_leaves(&self) -> Vec<Arc<dyn Specification<T>>> {
+        self.necessary_leaves().unwrap_or_default()
+    }
+
+    // The leaves the node needs, `None` when nothing satisfies it, so it needs everything.
+    fn necessary_leaves(&self) -> Option<Vec<Arc<dyn Specification<T>>>> {
+        let contains = |leaves: &[Arc<dyn Specification<T>>], leaf: &Arc<dyn Specification<T>>| {
+            let leaf = Self::Specification(leaf.clone());
+            leaves
+                .iter()
+                .any(|listed| Self::Specification(listed.clone()) == leaf)
+        };
+        match self {
+            Self::Specification(f) => Some(vec![f.clone()]),
+            Self::False => None,
+            Self::And(specifications) => {
+                let mut necessary: Vec<Arc<dyn Specification<T>>> = Vec::new();
+                for specification in specifications {
+                    for leaf in specification.necessary_leaves()? {
+                        if !contains(&necessary, &leaf) {
+                            necessary.push(leaf);
+                        }
+                    }
+                }
+                Some(necessary)
+            }
+            Self::Or(specifications) => Self::necessary_for(specifications, 1, contains),
+            Self::MinSatisfied(count, specifications) => {
+                Self::necessary_for(specifications, *count, contains)
+            }
+            _ => Some(vec![]),
+        }
+    }
+
+    // The leaves needed by at least `count` of the children: a leaf needed by fewer can be
+    // avoided by satisfying the `count` children that don't need it.
+    fn necessary_for(
+        specifications: &[Self],
+        count: usize,
+        contains: impl Fn(&[Arc<dyn Specification<T>>], &Arc<dyn Specification<T>>) -> bool,
+    ) -> Option<Vec<Arc<dyn Specification<T>>>> {
+        let children: Vec<Option<Vec<Arc<dyn Specification<T>>>>> = specifications
+            .iter()
+            .map(|specification| specification.necessary_leaves())
+            .collect();
+        if count == 0 {
+            return Some(vec![]);
+        }
+        if children.iter().flatten().count() < count {
+            return None;
+        }
+        let mut necessary: Vec<Arc<dyn Specification<T>>> = Vec::new();
+        for leaf in children.iter().flatten().flatten() {
+            let avoiding = children
+                .iter()
+                .flatten()
+                .filter(|leaves| !contains(leaves, leaf))
+                .count();
+            if avoiding < count && !contains(&necessary, leaf) {
+                necessary.push(leaf.clone());
+            }
+        }
+        Some(necessary)
+    }
+
+    /// Every combination of truth values of the `leaves`, taken as independent booleans,
+    /// under which the composition is satisfied: the true rows of its truth table. Each
+    /// assignment has a value per leaf, in the order of `leaves`, and the assignments are
+    /// in truth table order, counting up from all false with the first leaf most significant.
+    ///
+    /// Leaves match like in `==`, every leaf of the tree has to be listed.
+    pub fn satisfying_assignments(
+        &self,
+        leaves: &[Arc<dyn Specification<T>>],
+    ) -> Result<Vec<Vec<bool>>, AssignmentError> {
+        if leaves.len() > MAX_ASSIGNMENT_LEAVES {
+            return Err(AssignmentError::TooManyLeaves(leaves.len()));
+        }
+        let position = |leaf: &Arc<dyn Specification<T>>| {
+            let leaf = Self::Specification(leaf.clone());
+            leaves
+                .iter()
+                .position(|listed| Self::Specification(listed.clone()) == leaf)
+        };
+        let mut unlisted = None;
+        self.for_each_leaf(&mut |leaf| {
+            if unlisted.is_none() && position(leaf).is_none() {
+                unlisted = Some(leaf.name());
+            }
+        });
+        if let Some(name) = unlisted {
+            return Err(AssignmentError::UnlistedLeaf(name));
+        }
+        let assignments = (0..1_usize << leaves.len()).map(|row| {
+            (0..leaves.len())
+                .map(|i| row >> (leaves.len() - 1 - i) & 1 == 1)
+                .collect::<Vec<bool>>()
+        });
+        Ok(assignments
+            .filter(|values| {
+                self.evaluate_iteratively(None, &mut |leaf| {
+                    position(leaf).is_some_and(|position| values[position])
+                })
+                .unwrap_or_default()
+            })
+            .collect())
+    }
+
+    /// Paths, as child indexes from the root, of the subtrees that can't affect the outcome:
+    /// a `True` under an `And`, a `False` or a contradiction under an `Or` or a `Xor`.
+    /// A contradiction is an `And` with a child and its negation, or with a `False` child.
+    pub fn dead_branches(&self) -> Vec<Vec<usize>> {
+        let mut dead = Vec::new();
+        self.dead_branches_into(&mut vec![], &mut dead);
+        dead
+    }
+
+    fn dead_branches_into(&self, path: &mut Vec<usize>, dead: &mut Vec<Vec<usize>>) {
+        for (i, child) in self.children().into_iter().enumerate() {
+            path.push(i);
+            let is_dead = match self {
+                Self::And(..) => child == &Self::True,
+                Self::Or(..) | Self::Xor(..) => child.is_contradiction(),
+                _ => false,
+            };
+            if is_dead {
+                dead.push(path.clone());
+            } else {
+                child.dead_branches_into(path, dead);
+            }
+            path.pop();
+        }
+    }
+
+    fn is_contradiction(&self) -> bool {
+        match self {
+            Self::False => true,
+            Self::And(specifications) => specifications.iter().any(|specification| {
+                specification.is_contradiction()
+                    || specifications
+                        .iter()
+                        .any(|other| matches!(other, Self::Invert(inverted) if **inverted == *specification))
+            }),
+            _ => false,
+        }
+    }
+
+    /// Equality treating the children of `And`, `Or`, `Xor` and the counting thresholds
+    /// as multisets, as they are commutative. Nesting is still significant.
+    pub fn equals_modulo_order(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::And(a), Self::And(b))
+            | (Self::Or(a), Self::Or(b))
+            | (Self::Xor(a), Self::Xor(b)) => Self::children_equal_modulo_order(a, b),
+            (Self::MinSatisfied(a_count, a), Self::MinSatisfied(b_count, b))
+            | (Self::MaxSatisfied(a_count, a), Self::MaxSatisfied(b_count, b)) => {
+                a_count == b_count && Self::children_equal_modulo_order(a, b)
+            }
+            (Self::Invert(a), Self::Invert(b)) => a.equals_modulo_order(b),
+            _ => self == other,
+        }
+    }
+
+    fn children_equal_modulo_order(a: &[Self], b: &[Self]) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut matched = vec![false; b.len()];
+        a.iter().all(|specification| {
+            match (0..b.len()).find(|&j| !matched[j] && specification.equals_modulo_order(&b[j])) {
+                Some(j) => {
+                    matched[j] = true;
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+
+    fn diff_into(
+        &self,
+        other: &Self,
+        path: &mut Vec<usize>,
+        other_path: &mut Vec<usize>,
+        entries: &mut Vec<DiffEntry>,
+    ) {
+        if self == other {
+            return;
+        }
+        match (self, other) {
+            (Self::And(a), Self::And(b))
+            | (Self::Or(a), Self::Or(b))
+            | (Self::Xor(a), Self::Xor(b)) => Self::diff_children(a, b, path, other_path, entries),
+            (Self::MinSatisfied(a_count, a), Self::MinSatisfied(b_count, b))
+            | (Self::MaxSatisfied(a_count, a), Self::MaxSatisfied(b_count, b))
+                if a_count == b_count =>
+            {
+                Self::diff_children(a, b, path, other_path, entries)
+            }
+            (Self::Invert(a), Self::Invert(b)) => {
+                path.push(0);
+                other_path.push(0);
+                a.diff_into(b, path, other_path, entries);
+                path.pop();
+                other_path.pop();
+            }
+            _ => entries.push(DiffEntry::Changed {
+                path: path.clone(),
+                from: self.to_string(),
+                to: other.to_string(),
+            }),
+        }
+    }
+
+    fn diff_children(
+        specifications: &[Self],
+        others: &[Self],
+        path: &mut Vec<usize>,
+        other_path: &mut Vec<usize>,
+        entries: &mut Vec<DiffEntry>,
+    ) {
+        let mut matched = vec![false; others.len()];
+        let mut removed = Vec::new();
+        for (i, specification) in specifications.iter().enumerate() {
+            match (0..others.len()).find(|&j| !matched[j] && &others[j] == specification) {
+                Some(j) => matched[j] = true,
+                None => removed.push(i),
+            }
+        }
+        let mut leftover = Vec::new();
+        for i in removed {
+            let kind = std::mem::discriminant(&specifications[i]);
+            let counterpart = (0..others.len()).find(|&j| {
+                !matched[j]
+                    && std::mem::discriminant(&others[j]) == kind
+                    && !matches!(
+                        others[j],
+                        Self::Specification(..) | Self::True | Self::False
+                    )
+            });
+            match counterpart {
+                Some(j) => {
+                    matched[j] = true;
+                    path.push(i);
+                    other_path.push(j);
+                    specifications[i].diff_into(&others[j], path, other_path, entries);
+                    path.pop();
+                    other_path.pop();
+                }
+                None => leftover.push(i),
+            }
+        }
+        for i in leftover {
+            let mut removed_path = path.clone();
+            removed_path.push(i);
+            entries.push(DiffEntry::Removed {
+                path: removed_path,
+                node: specifications[i].to_string(),
+            });
+        }
+        for (j, other) in others.iter().enumerate() {
+            if !matched[j] {
+                let mut added_path = other_path.clone();
+                added_path.push(j);
+                entries.push(DiffEntry::Added {
+                    path: added_path,
+                    node: other.to_string(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_distance_to_satisfy_and() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(Zero {});
+
+        assert_eq!(specification.distance_to_satisfy(&20), 2);
+        assert_eq!(specification.distance_to_satisfy(&0), 1);
+    }
+
+    #[test]
+    fn test_distance_to_satisfy_nested() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(Zero {})
+            .or(Zero {}.invert().and(GreaterThan { value: 100 }));
+
+        assert_eq!(specification.distance_to_satisfy(&20), 1);
+        assert_eq!(specification.distance_to_satisfy(&0), 1);
+        assert_eq!(specification.distance_to_satisfy(&101), 0);
+        assert_eq!(
+            SpecificationCompositions::And(vec![
+                GreaterThan { value: 5 }.composite(),
+                SpecificationCompositions::False,
+            ])
+            .distance_to_satisfy(&6),
+            usize::MAX
+        );
+    }
+
+    #[test]
+    fn test_assign_ids() {
+        let specification = SpecificationCompositions::And(vec![
+            GreaterThan { value: 5 }.composite(),
+            LessThan { value: 10 }.or(Zero {}.invert()),
+            SpecificationCompositions::True,
+        ]);
+
+        let tree = specification.assign_ids();
+
+        assert_eq!(tree.ids(), (0..7).map(NodeId).collect::<Vec<_>>());
+        assert_eq!(specification.assign_ids().ids(), tree.ids());
+        let not = tree.find(NodeId(4)).unwrap();
+        assert_eq!(not.name, "not");
+        assert!(not.leaf.is_none());
+        assert_eq!(not.children[0].id, NodeId(5));
+        assert_eq!(not.children[0].leaf.as_ref().unwrap().name(), "Zero");
+        assert!(tree.find(NodeId(7)).is_none());
+    }
+
+    fn leaves() -> Vec<Arc<dyn Specification<i32>>> {
+        vec![
+            Arc::new(GreaterThan { value: 5 }),
+            Arc::new(LessThan { value: 10 }),
+        ]
+    }
+
+    #[test]
+    fn test_satisfying_assignments_or() {
+        let leaves = leaves();
+        let specification = SpecificationCompositions::Or(vec![
+            SpecificationCompositions::Specification(leaves[0].clone()),
+            SpecificationCompositions::Specification(leaves[1].clone()),
+        ]);
+
+        assert_eq!(
+            specification.satisfying_assignments(&leaves),
+            Ok(vec![vec![false, true], vec![true, false], vec![true, true]])
+        );
+    }
+
+    #[test]
+    fn test_satisfying_assignments_by_equality() {
+        let specification = GreaterThan { value: 5 }.xor(LessThan { value: 10 }.invert());
+
+        assert_eq!(
+            specification.satisfying_assignments(&leaves()),
+            Ok(vec![vec![false, false], vec![true, true]])
+        );
+    }
+
+    #[test]
+    fn test_satisfying_assignments_errors() {
+        let too_many: Vec<Arc<dyn Specification<i32>>> = (0..=MAX_ASSIGNMENT_LEAVES as i32)
+            .map(|value| Arc::new(GreaterThan { value }) as Arc<dyn Specification<i32>>)
+            .collect();
+
+        assert_eq!(
+            Zero {}.composite().satisfying_assignments(&too_many),
+            Err(AssignmentError::TooManyLeaves(21))
+        );
+        assert_eq!(
+            GreaterThan { value: 5 }
+                .and(Zero {})
+                .satisfying_assignments(&leaves()),
+            Err(AssignmentError::UnlistedLeaf("Zero".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_critical_leaves() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 }.or(Zero {}));
+
+        let critical = specification.critical_leaves();
+
+        assert_eq!(critical.len(), 1);
+        assert_eq!(critical[0].name(), "GreaterThan");
+    }
+
+    #[test]
+    fn test_critical_leaves_branches() {
+        let names = |specification: SpecificationCompositions<i32>| -> Vec<String> {
+            specification
+                .critical_leaves()
+                .iter()
+                .map(|leaf| leaf.name())
+                .collect()
+        };
+
+        let required_by_every_branch = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .or(GreaterThan { value: 5 }.and(Zero {}));
+        assert_eq!(names(required_by_every_branch), ["GreaterThan"]);
+
+        let absorbed = GreaterThan { value: 5 }.or(GreaterThan { value: 5 }.and(Zero {}));
+        assert_eq!(names(absorbed), ["GreaterThan"]);
+
+        let dead_branch = SpecificationCompositions::Or(vec![
+            SpecificationCompositions::False,
+            Zero {}.composite(),
+        ]);
+        assert_eq!(names(dead_branch), ["Zero"]);
+        assert!(names(Zero {}.xor(LessThan { value: 10 })).is_empty());
+        assert!(names(SpecificationCompositions::False).is_empty());
+
+        let many = SpecificationCompositions::And(
+            (0..100)
+                .map(|value| GreaterThan { value }.composite())
+                .collect(),
+        );
+        assert_eq!(many.critical_leaves().len(), 100);
+    }
+
+    #[test]
+    fn test_critical_leaves_nested() {
+        let specification = SpecificationCompositions::And(vec![
+            SpecificationCompositions::MinSatisfied(
+                2,
+                vec![greater_than_5().composite(), less_than_10().composite()],
+            ),
+            SpecificationCompositions::MinSatisfied(
+                1,
+                vec![GreaterThan { value: 1 }.composite(), Zero {}.composite()],
+            ),
+            Zero {}.invert(),
+        ]);
+
+        let names: Vec<String> = specification
+            .critical_leaves()
+            .iter()
+            .map(|leaf| leaf.name())
+            .collect();
+
+        assert_eq!(names, ["GreaterThan5", "LessThan10"]);
+        assert_eq!(Zero {}.composite().critical_leaves().len(), 1);
+        assert!(Zero {}.invert().critical_leaves().is_empty());
+    }
+
+    #[test]
+    fn test_dead_branches() {
+        let specification = SpecificationCompositions::Or(vec![
+            GreaterThan { value: 5 }.composite(),
+            SpecificationCompositions::False,
+        ])
+        .and(SpecificationCompositions::And(vec![
+            LessThan { value: 10 }.composite(),
+            SpecificationCompositions::True,
+        ]))
+        .and(Zero {}.xor(GreaterThan { value: 1 }.and(GreaterThan { value: 1 }.invert())));
+
+        assert_eq!(
+            specification.dead_branches(),
+            vec![vec![0, 1], vec![1, 1], vec![2, 1]]
+        );
+    }
+
+    #[test]
+    fn test_dead_branches_none() {
+        let specification = GreaterThan { value: 5 }.or(Zero {}.and(LessThan { value: 3 }));
+
+        assert!(specification.dead_branches().is_empty());
+    }
+
+    #[test]
+    fn test_equals_modulo_order() {
+        let a = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 }.or(Zero {}))
+            .and(Zero {});
+        let b = Zero {}
+            .and(Zero {}.or(LessThan { value: 10 }))
+            .and(GreaterThan { value: 5 });
+
+        assert!(a.equals_modulo_order(&b));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_not_equals_modulo_order() {
+        let a = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(Zero {});
+        let different_leaf = GreaterThan { value: 5 }
+            .and(LessThan { value: 11 })
+            .and(Zero {});
+        let different_kind = GreaterThan { value: 5 }
+            .or(LessThan { value: 10 })
+            .or(Zero {});
+        let different_count = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(LessThan { value: 10 });
+        let different_nesting =
+            GreaterThan { value: 5 }.and(LessThan { value: 10 }.and(Zero {}).invert());
+
+        assert!(!a.equals_modulo_order(&different_leaf));
+        assert!(!a.equals_modulo_order(&different_kind));
+        assert!(!a.equals_modulo_order(&different_count));
+        assert!(!a.equals_modulo_order(&different_nesting));
+    }
+
+    #[test]
+    fn test_diff_same() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 });
+        let reordered = LessThan { value: 10 }.and(GreaterThan { value: 5 });
+
+        assert!(specification.diff(&specification.clone()).is_empty());
+        assert!(specification.diff(&reordered).is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = GreaterThan { value: 5 }.and(
+            LessThan { value: 10 }
+                .or(Zero {})
+                .or(GreaterThan { value: 20 }),
+        );
+        let new = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 }.or(Zero {}))
+            .and(LessThan { value: 30 });
+
+        let diff = old.diff(&new);
+
+        assert_eq!(
+            diff.entries,
+            vec![
+                DiffEntry::Removed {
+                    path: vec![1, 2],
+                    node: "GreaterThan { value: 20 }".to_string()
+                },
+                DiffEntry::Added {
+                    path: vec![2],
+                    node: "LessThan { value: 30 }".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "- [1, 2] GreaterThan { value: 20 }\n+ [2] LessThan { value: 30 }\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_changed() {
+        let old = GreaterThan { value: 5 }.invert();
+        let new = GreaterThan { value: 5 }.or(Zero {});
+
+        assert_eq!(
+            old.diff(&new).entries,
+            vec![DiffEntry::Changed {
+                path: vec![],
+                from: "not GreaterThan { value: 5 }".to_string(),
+                to: "(GreaterThan { value: 5 } or Zero)".to_string()
+            }]
+        );
+    }
+}
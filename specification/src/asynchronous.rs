@@ -0,0 +1,176 @@
+// Specifications whose evaluation has to wait, e.g. on a remote service, and evaluating
+// them over asynchronous streams of candidates, such as paginated API results.
+//
+// There is no stream in std, `Stream` has the shape of `futures::Stream`, so adapting
+// streams of either kind to the other only takes forwarding `poll_next`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+/// A specification evaluated asynchronously.
+pub trait AsyncSpecification<T> {
+    fn is_satisfied_by(&self, candidate: &T) -> impl Future<Output = bool>;
+}
+
+/// An asynchronous sequence of items, polled like `futures::Stream`.
+pub trait Stream {
+    type Item;
+
+    /// The next item when it is ready, `None` once the stream is exhausted.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+}
+
+/// Yields the items of `stream` satisfying `specification`, in order, awaiting the
+/// evaluation of each item before pulling the next one.
+pub fn filter_stream<T, S, St>(specification: &S, stream: St) -> FilterStream<'_, T, S, St>
+where
+    S: AsyncSpecification<T>,
+    St: Stream<Item = T> + Unpin,
+{
+    FilterStream {
+        specification,
+        stream,
+        pending: None,
+    }
+}
+
+// The evaluation of an item, handing the item back with the result.
+type Evaluation<'a, T> = Pin<Box<dyn Future<Output = (T, bool)> + 'a>>;
+
+/// The stream returned by [`filter_stream`].
+pub struct FilterStream<'a, T, S, St> {
+    specification: &'a S,
+    stream: St,
+    // The item being evaluated, owned by its evaluation until it is done.
+    pending: Option<Evaluation<'a, T>>,
+}
+
+impl<'a, T: 'a, S, St> Stream for FilterStream<'a, T, S, St>
+where
+    S: AsyncSpecification<T>,
+    St: Stream<Item = T> + Unpin,
+{
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(pending) = &mut this.pending {
+                let (candidate, satisfied) = ready!(pending.as_mut().poll(cx));
+                this.pending = None;
+                if satisfied {
+                    return Poll::Ready(Some(candidate));
+                }
+            }
+            match ready!(Pin::new(&mut this.stream).poll_next(cx)) {
+                Some(candidate) => {
+                    let specification = this.specification;
+                    this.pending = Some(Box::pin(async move {
+                        let satisfied = specification.is_satisfied_by(&candidate).await;
+                        (candidate, satisfied)
+                    }));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::future::poll_fn;
+    use std::pin::pin;
+    use std::task::Waker;
+
+    // Drives the future to completion, polling it again right away whenever it is pending.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    async fn collect<St: Stream + Unpin>(mut stream: St) -> Vec<St::Item> {
+        let mut items = Vec::new();
+        while let Some(item) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            items.push(item);
+        }
+        items
+    }
+
+    // Pending once before every result, like a response still on its way.
+    async fn yield_now() {
+        let mut yielded = false;
+        poll_fn(|_| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    // Pages of results, each page pending once before its items are available.
+    struct Pages {
+        pages: Vec<Vec<i32>>,
+        ready: bool,
+    }
+
+    impl Stream for Pages {
+        type Item = i32;
+
+        fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<i32>> {
+            let this = self.get_mut();
+            while this.pages.first().is_some_and(Vec::is_empty) {
+                this.pages.remove(0);
+                this.ready = false;
+            }
+            if this.pages.is_empty() {
+                return Poll::Ready(None);
+            }
+            if !this.ready {
+                this.ready = true;
+                return Poll::Pending;
+            }
+            Poll::Ready(Some(this.pages[0].remove(0)))
+        }
+    }
+
+    struct Even;
+
+    impl AsyncSpecification<i32> for Even {
+        async fn is_satisfied_by(&self, candidate: &i32) -> bool {
+            yield_now().await;
+            candidate % 2 == 0
+        }
+    }
+
+    #[test]
+    fn test_filter_stream() {
+        let pages = Pages {
+            pages: vec![vec![1, 2, 3], vec![], vec![4, 6, 7]],
+            ready: false,
+        };
+
+        let filtered = block_on(collect(filter_stream(&Even, pages)));
+
+        assert_eq!(filtered, vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn test_filter_stream_empty() {
+        let pages = Pages {
+            pages: vec![vec![1, 3]],
+            ready: false,
+        };
+
+        assert!(block_on(collect(filter_stream(&Even, pages))).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+// Leaves validating raw binary payloads, for owned buffers and borrowed slices alike.
+
+use crate::Specification;
+
+/// Satisfied by payloads starting with `prefix`, an empty prefix is satisfied by anything.
+pub fn starts_with(prefix: Vec<u8>) -> StartsWith {
+    StartsWith { prefix }
+}
+
+/// Satisfied by payloads ending with `suffix`, an empty suffix is satisfied by anything.
+pub fn ends_with(suffix: Vec<u8>) -> EndsWith {
+    EndsWith { suffix }
+}
+
+/// Satisfied by payloads from `lo` to `hi` bytes long, both included.
+pub fn len_between(lo: usize, hi: usize) -> LenBetween {
+    LenBetween { lo, hi }
+}
+
+#[derive(Debug, Clone)]
+pub struct StartsWith {
+    prefix: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EndsWith {
+    suffix: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LenBetween {
+    lo: usize,
+    hi: usize,
+}
+
+impl StartsWith {
+    fn matches(&self, payload: &[u8]) -> bool {
+        payload.starts_with(&self.prefix)
+    }
+}
+
+impl EndsWith {
+    fn matches(&self, payload: &[u8]) -> bool {
+        payload.ends_with(&self.suffix)
+    }
+}
+
+impl LenBetween {
+    fn matches(&self, payload: &[u8]) -> bool {
+        (self.lo..=self.hi).contains(&payload.len())
+    }
+}
+
+macro_rules! bytes_specification {
+    ($type:ty) => {
+        impl Specification<Vec<u8>> for $type {
+            fn is_satisfied_by(&self, candidate: &Vec<u8>) -> bool {
+                self.matches(candidate)
+            }
+            fn eq_by_debug(&self) -> bool {
+                true
+            }
+        }
+
+        impl Specification<&[u8]> for $type {
+            fn is_satisfied_by(&self, candidate: &&[u8]) -> bool {
+                self.matches(candidate)
+            }
+            fn eq_by_debug(&self) -> bool {
+                true
+            }
+        }
+    };
+}
+
+bytes_specification!(StartsWith);
+bytes_specification!(EndsWith);
+bytes_specification!(LenBetween);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    #[test]
+    fn test_starts_with() {
+        let specification = starts_with(PNG.to_vec());
+
+        assert!(specification.is_satisfied_by(&[PNG, b"data"].concat()));
+        assert!(!specification.is_satisfied_by(&b"GIF89a".to_vec()));
+        assert!(!specification.is_satisfied_by(&Vec::new()));
+        assert!(starts_with(vec![]).is_satisfied_by(&Vec::new()));
+    }
+
+    #[test]
+    fn test_ends_with() {
+        let specification = ends_with(b"\r\n".to_vec());
+
+        assert!(specification.is_satisfied_by(&b"line\r\n".as_slice()));
+        assert!(!specification.is_satisfied_by(&b"line\n".as_slice()));
+        assert!(!specification.is_satisfied_by(&b"".as_slice()));
+        assert!(ends_with(vec![]).is_satisfied_by(&b"".as_slice()));
+    }
+
+    #[test]
+    fn test_len_between() {
+        let specification = len_between(1, 4);
+
+        assert!(specification.is_satisfied_by(&vec![0]));
+        assert!(specification.is_satisfied_by(&b"four".as_slice()));
+        assert!(!specification.is_satisfied_by(&b"five!".as_slice()));
+        assert!(!specification.is_satisfied_by(&Vec::new()));
+        assert!(len_between(0, 0).is_satisfied_by(&b"".as_slice()));
+    }
+
+    #[test]
+    fn test_composed() {
+        let specification = starts_with(PNG.to_vec()).and(len_between(0, 16));
+
+        assert!(specification.is_satisfied_by(&PNG.to_vec()));
+        assert!(!specification.is_satisfied_by(&[PNG, &[0; 16]].concat()));
+    }
+}
@@ -0,0 +1,682 @@
+// Wrappers remembering the result of a specification per candidate,
+// for specifications that are expensive to evaluate.
+//
+// The caches are only locked to look up and to record a result, never while the wrapped
+// specification evaluates, so a slow evaluation doesn't hold up the other candidates. The
+// price is that a candidate missed by two threads at once is evaluated by both.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Specification, SpecificationCompositions};
+
+/// Caches the results of `specification`, keeping at most `capacity` candidates
+/// and evicting the least recently used one first.
+#[derive(Debug)]
+pub struct LruCached<T, S> {
+    specification: S,
+    capacity: usize,
+    cache: Mutex<Lru<T>>,
+}
+
+#[derive(Debug)]
+struct Lru<T> {
+    // The result of every candidate, with the generation it was last used in.
+    results: HashMap<T, (bool, u64)>,
+    // The candidates by the generation they were last used in, least recently used first.
+    order: BTreeMap<u64, T>,
+    generation: u64,
+}
+
+impl<T: Hash + Eq + Clone> Lru<T> {
+    // The remembered result of the candidate, making it the most recently used.
+    fn touch(&mut self, candidate: &T) -> Option<bool> {
+        let (satisfied, used) = self.results.get_mut(candidate)?;
+        self.generation += 1;
+        let previous = std::mem::replace(used, self.generation);
+        if let Some(candidate) = self.order.remove(&previous) {
+            self.order.insert(self.generation, candidate);
+        }
+        Some(*satisfied)
+    }
+
+    fn insert(&mut self, candidate: &T, satisfied: bool, capacity: usize) {
+        if let Some((_, used)) = self.results.get(candidate) {
+            self.order.remove(used);
+        } else if self.results.len() >= capacity {
+            if let Some((_, evicted)) = self.order.pop_first() {
+                self.results.remove(&evicted);
+            }
+        }
+        self.generation += 1;
+        self.results
+            .insert(candidate.clone(), (satisfied, self.generation));
+        self.order.insert(self.generation, candidate.clone());
+    }
+}
+
+impl<T: Hash + Eq + Clone, S> LruCached<T, S> {
+    pub fn new(specification: S, capacity: usize) -> Self {
+        Self {
+            specification,
+            capacity,
+            cache: Mutex::new(Lru {
+                results: HashMap::new(),
+                order: BTreeMap::new(),
+                generation: 0,
+            }),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.results.clear();
+        cache.order.clear();
+    }
+}
+
+impl<T, S> Specification<T> for LruCached<T, S>
+where
+    T: std::fmt::Debug + Hash + Eq + Clone,
+    S: Specification<T>,
+{
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        let cached = self.cache.lock().unwrap().touch(candidate);
+        if let Some(satisfied) = cached {
+            return satisfied;
+        }
+        let satisfied = self.specification.is_satisfied_by(candidate);
+        if self.capacity > 0 {
+            self.cache
+                .lock()
+                .unwrap()
+                .insert(candidate, satisfied, self.capacity);
+        }
+        satisfied
+    }
+
+    fn name(&self) -> String {
+        self.specification.name()
+    }
+
+    fn cost(&self) -> u32 {
+        self.specification.cost()
+    }
+
+    fn importance(&self) -> u32 {
+        self.specification.importance()
+    }
+}
+
+/// Caches the result of a whole composition per candidate, so evaluating the same candidate
+/// again skips the traversal entirely. Unlike [`LruCached`], which wraps a single
+/// specification, typically an expensive leaf, this keeps every candidate it has seen.
+#[derive(Debug)]
+pub struct Memoized<T: std::fmt::Debug> {
+    specification: SpecificationCompositions<T>,
+    results: Mutex<HashMap<T, bool>>,
+}
+
+impl<T: std::fmt::Debug + Hash + Eq> SpecificationCompositions<T> {
+    pub fn memoized(self) -> Memoized<T> {
+        Memoized {
+            specification: self,
+            results: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> Memoized<T> {
+    pub fn len(&self) -> usize {
+        self.results.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.results.lock().unwrap().clear();
+    }
+}
+
+impl<T: std::fmt::Debug + Hash + Eq + Clone> Specification<T> for Memoized<T> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        let cached = self.results.lock().unwrap().get(candidate).copied();
+        if let Some(satisfied) = cached {
+            return satisfied;
+        }
+        let satisfied = self.specification.is_satisfied_by(candidate);
+        self.results
+            .lock()
+            .unwrap()
+            .insert(candidate.clone(), satisfied);
+        satisfied
+    }
+
+    fn name(&self) -> String {
+        self.specification.name()
+    }
+
+    fn cost(&self) -> u32 {
+        self.specification.cost()
+    }
+
+    fn importance(&self) -> u32 {
+        self.specification.importance()
+    }
+}
+
+/// Source of the current time, injectable so time dependent code can be tested.
+pub trait Clock: std::fmt::Debug {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Caches the results of `specification` for `ttl`, re-evaluating a candidate once
+/// its result expired. Suits specifications whose underlying data refreshes periodically.
+#[derive(Debug)]
+pub struct TtlCached<T, S, C = SystemClock> {
+    specification: S,
+    ttl: Duration,
+    clock: C,
+    cache: Mutex<HashMap<T, (bool, Instant)>>,
+}
+
+impl<T: Hash + Eq, S> TtlCached<T, S> {
+    pub fn new(specification: S, ttl: Duration) -> Self {
+        Self::with_clock(specification, ttl, SystemClock)
+    }
+}
+
+impl<T: Hash + Eq, S, C: Clock> TtlCached<T, S, C> {
+    pub fn with_clock(specification: S, ttl: Duration, clock: C) -> Self {
+        Self {
+            specification,
+            ttl,
+            clock,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub const fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+}
+
+impl<T, S, C> Specification<T> for TtlCached<T, S, C>
+where
+    T: std::fmt::Debug + Hash + Eq + Clone,
+    S: Specification<T>,
+    C: Clock,
+{
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        let now = self.clock.now();
+        let cached = self.cache.lock().unwrap().get(candidate).copied();
+        if let Some((satisfied, evaluated_at)) = cached {
+            if now.duration_since(evaluated_at) < self.ttl {
+                return satisfied;
+            }
+        }
+        let satisfied = self.specification.is_satisfied_by(candidate);
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(candidate.clone(), (satisfied, now));
+        satisfied
+    }
+
+    fn name(&self) -> String {
+        self.specification.name()
+    }
+
+    fn cost(&self) -> u32 {
+        self.specification.cost()
+    }
+
+    fn importance(&self) -> u32 {
+        self.specification.importance()
+    }
+}
+
+/// Remembers the candidates failing `specification`, rejecting them again without evaluating,
+/// while the candidates not known to fail are evaluated every time.
+///
+/// Suits specifications whose failures are expensive and stable, e.g. a background check
+/// that came back negative, while a passing result may still be revoked. The price is
+/// staleness: a remembered failure stands even if the candidate would pass by now,
+/// until the cache is cleared.
+#[derive(Debug)]
+pub struct CacheFailures<T, S> {
+    specification: S,
+    failures: Mutex<HashSet<T>>,
+}
+
+impl<T: Hash + Eq, S> CacheFailures<T, S> {
+    pub fn new(specification: S) -> Self {
+        Self {
+            specification,
+            failures: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The number of failed candidates remembered.
+    pub fn len(&self) -> usize {
+        self.failures.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.failures.lock().unwrap().clear();
+    }
+}
+
+impl<T, S> Specification<T> for CacheFailures<T, S>
+where
+    T: std::fmt::Debug + Hash + Eq + Clone,
+    S: Specification<T>,
+{
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        if self.failures.lock().unwrap().contains(candidate) {
+            return false;
+        }
+        let satisfied = self.specification.is_satisfied_by(candidate);
+        if !satisfied {
+            self.failures.lock().unwrap().insert(candidate.clone());
+        }
+        satisfied
+    }
+
+    fn name(&self) -> String {
+        self.specification.name()
+    }
+
+    fn cost(&self) -> u32 {
+        self.specification.cost()
+    }
+
+    fn importance(&self) -> u32 {
+        self.specification.importance()
+    }
+}
+
+/// Identifies a leaf of a [`CachedEvaluation`], by its position among the leaves in pre-order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LeafId(pub usize);
+
+/// Keeps the result of every leaf for a candidate that changes a little at a time, e.g. an
+/// interactive form, so only the leaves depending on what changed need to run again.
+#[derive(Debug)]
+pub struct CachedEvaluation<T: std::fmt::Debug> {
+    specification: SpecificationCompositions<T>,
+    results: Vec<Option<bool>>,
+}
+
+impl<T: std::fmt::Debug> CachedEvaluation<T> {
+    pub fn new(specification: SpecificationCompositions<T>) -> Self {
+        let mut leaves = 0;
+        specification.for_each_leaf(&mut |_| leaves += 1);
+        Self {
+            specification,
+            results: vec![None; leaves],
+        }
+    }
+
+    /// The leaves with their ids, to find the ones depending on a field.
+    pub fn leaves(&self) -> Vec<(LeafId, Arc<dyn Specification<T>>)> {
+        let mut leaves = Vec::new();
+        self.specification.for_each_leaf(&mut |leaf| {
+            leaves.push((LeafId(leaves.len()), leaf.clone()));
+        });
+        leaves
+    }
+
+    /// Evaluates the `candidate`, running only the `dirty` leaves and the ones without a
+    /// result yet, reusing the remembered results of the others.
+    ///
+    /// Every leaf runs on the first evaluation; there is no short-circuiting,
+    /// so every leaf keeps a result to reuse.
+    pub fn reevaluate_if(&mut self, candidate: &T, dirty: &[LeafId]) -> bool {
+        let mut next = 0;
+        Self::evaluate_node(
+            &self.specification,
+            candidate,
+            dirty,
+            &mut self.results,
+            &mut next,
+        )
+    }
+
+    fn evaluate_node(
+        node: &SpecificationCompositions<T>,
+        candidate: &T,
+        dirty: &[LeafId],
+        results: &mut [Option<bool>],
+        next: &mut usize,
+    ) -> bool {
+        if let SpecificationCompositions::Specification(f) = node {
+            let id = *next;
+            *next += 1;
+            return match results[id] {
+                Some(satisfied) if !dirty.contains(&LeafId(id)) => satisfied,
+                _ => {
+                    let satisfied = f.is_satisfied_by(candidate);
+                    results[id] = Some(satisfied);
+                    satisfied
+                }
+            };
+        }
+        let children: Vec<bool> = node
+            .children()
+            .into_iter()
+            .map(|child| Self::evaluate_node(child, candidate, dirty, results, next))
+            .collect();
+        let satisfied = children.iter().filter(|&&satisfied| satisfied).count();
+        match node {
+            SpecificationCompositions::And(..) => satisfied == children.len(),
+            SpecificationCompositions::Or(..) => satisfied > 0,
+            SpecificationCompositions::Xor(..) => satisfied % 2 == 1,
+            SpecificationCompositions::Invert(..) => !children[0],
+            SpecificationCompositions::True => true,
+            SpecificationCompositions::False | SpecificationCompositions::Specification(..) => {
+                false
+            }
+            SpecificationCompositions::WeightedAtLeast(threshold, specifications) => {
+                let weight: f64 = specifications
+                    .iter()
+                    .zip(&children)
+                    .filter(|(_, &satisfied)| satisfied)
+                    .map(|((weight, _), _)| weight)
+                    .sum();
+                weight >= *threshold
+            }
+            SpecificationCompositions::MinSatisfied(count, _) => satisfied >= *count,
+            SpecificationCompositions::MaxSatisfied(count, _) => satisfied <= *count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[derive(Debug, Clone)]
+    struct FakeClock {
+        now: Arc<Mutex<Instant>>,
+    }
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self {
+                now: Arc::new(Mutex::new(Instant::now())),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            *self.now.lock().unwrap()
+        }
+    }
+
+    // Satisfied by 0 right away, by anything else once released, signalling when it started.
+    #[derive(Debug)]
+    struct Handshake {
+        started: Mutex<mpsc::Sender<()>>,
+        released: Mutex<mpsc::Receiver<()>>,
+    }
+
+    impl Specification<i32> for Handshake {
+        fn is_satisfied_by(&self, candidate: &i32) -> bool {
+            if *candidate == 0 {
+                return true;
+            }
+            self.started.lock().unwrap().send(()).unwrap();
+            let released = self.released.lock().unwrap();
+            released.recv_timeout(Duration::from_secs(5)).is_ok()
+        }
+    }
+
+    fn handshake() -> (Handshake, mpsc::Receiver<()>, mpsc::Sender<()>) {
+        let (started, on_started) = mpsc::channel();
+        let (release, released) = mpsc::channel();
+        let specification = Handshake {
+            started: Mutex::new(started),
+            released: Mutex::new(released),
+        };
+        (specification, on_started, release)
+    }
+
+    // Evaluates 0 while 1 is being evaluated on another thread, which would time out
+    // waiting for the release if the cache stayed locked during the evaluation.
+    fn assert_unlocked_while_evaluating(
+        cached: &(impl Specification<i32> + Sync),
+        started: &mpsc::Receiver<()>,
+        release: &mpsc::Sender<()>,
+    ) {
+        assert!(cached.is_satisfied_by(&0));
+        thread::scope(|scope| {
+            let evaluating = scope.spawn(|| cached.is_satisfied_by(&1));
+            started.recv().unwrap();
+            assert!(cached.is_satisfied_by(&0));
+            release.send(()).unwrap();
+            assert!(evaluating.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn test_lru_cached_hit() {
+        let (specification, evaluations) = counting(Zero {}.invert());
+        let cached = LruCached::new(specification, 2);
+
+        assert!(cached.is_satisfied_by(&2));
+        assert!(cached.is_satisfied_by(&2));
+        assert!(!cached.is_satisfied_by(&0));
+
+        assert_eq!(count(&evaluations), 2);
+        assert_eq!(cached.len(), 2);
+    }
+
+    #[test]
+    fn test_lru_cached_eviction() {
+        let (specification, evaluations) = counting(Zero {}.invert());
+        let cached = LruCached::new(specification, 2);
+
+        cached.is_satisfied_by(&1);
+        cached.is_satisfied_by(&2);
+        // 1 becomes the most recently used, so 2 gets evicted.
+        cached.is_satisfied_by(&1);
+        cached.is_satisfied_by(&3);
+        assert_eq!(cached.len(), cached.capacity());
+        assert_eq!(count(&evaluations), 3);
+
+        cached.is_satisfied_by(&1);
+        assert_eq!(count(&evaluations), 3);
+
+        cached.is_satisfied_by(&2);
+        assert_eq!(count(&evaluations), 4);
+    }
+
+    #[test]
+    fn test_lru_cached_recency() {
+        let (specification, evaluations) = counting(Zero {}.invert());
+        let cached = LruCached::new(specification, 3);
+
+        for candidate in [1, 2, 3, 1, 2, 1] {
+            cached.is_satisfied_by(&candidate);
+        }
+        // 3 is the least recently used, then 2.
+        cached.is_satisfied_by(&4);
+        cached.is_satisfied_by(&5);
+        assert_eq!(count(&evaluations), 5);
+
+        cached.is_satisfied_by(&1);
+        assert_eq!(count(&evaluations), 5);
+        cached.is_satisfied_by(&3);
+        cached.is_satisfied_by(&2);
+        assert_eq!(count(&evaluations), 7);
+        assert_eq!(cached.len(), 3);
+    }
+
+    #[test]
+    fn test_lru_cached_unlocked_while_evaluating() {
+        let (specification, started, release) = handshake();
+        let cached = LruCached::new(specification, 2);
+
+        assert_unlocked_while_evaluating(&cached, &started, &release);
+        assert_eq!(cached.len(), 2);
+    }
+
+    #[test]
+    fn test_lru_cached_clear() {
+        let (specification, evaluations) = counting(Zero {}.invert());
+        let cached = LruCached::new(specification, 2);
+
+        cached.is_satisfied_by(&1);
+        cached.clear();
+        assert!(cached.is_empty());
+
+        cached.is_satisfied_by(&1);
+        assert_eq!(count(&evaluations), 2);
+    }
+
+    #[test]
+    fn test_memoized() {
+        let (greater_than, greater_than_evaluations) = counting(GreaterThan { value: 5 });
+        let (less_than, less_than_evaluations) = counting(LessThan { value: 10 });
+        let memoized = greater_than.and(less_than).memoized();
+
+        assert!(memoized.is_satisfied_by(&7));
+        assert!(memoized.is_satisfied_by(&7));
+        assert!(!memoized.is_satisfied_by(&12));
+        assert!(!memoized.is_satisfied_by(&12));
+
+        assert_eq!(count(&greater_than_evaluations), 2);
+        assert_eq!(count(&less_than_evaluations), 2);
+        assert_eq!(memoized.len(), 2);
+        memoized.clear();
+        assert!(memoized.is_empty());
+    }
+
+    #[test]
+    fn test_cache_failures() {
+        let (specification, evaluations) = counting(Zero {}.invert());
+        let cached = CacheFailures::new(specification);
+
+        assert!(!cached.is_satisfied_by(&0));
+        assert!(!cached.is_satisfied_by(&0));
+        assert_eq!(count(&evaluations), 1);
+
+        assert!(cached.is_satisfied_by(&1));
+        assert!(cached.is_satisfied_by(&1));
+        assert_eq!(count(&evaluations), 3);
+
+        assert_eq!(cached.len(), 1);
+        cached.clear();
+        assert!(cached.is_empty());
+        assert!(!cached.is_satisfied_by(&0));
+        assert_eq!(count(&evaluations), 4);
+    }
+
+    #[test]
+    fn test_cache_failures_unlocked_while_evaluating() {
+        let (specification, started, release) = handshake();
+        let cached = CacheFailures::new(specification);
+
+        assert_unlocked_while_evaluating(&cached, &started, &release);
+    }
+
+    #[test]
+    fn test_cached_evaluation() {
+        let (greater_than, greater_than_evaluations) = counting(GreaterThan { value: 5 });
+        let (less_than, less_than_evaluations) = counting(LessThan { value: 10 });
+        let mut evaluation = CachedEvaluation::new(greater_than.and(less_than.invert()));
+        let leaves = evaluation.leaves();
+        assert_eq!(leaves.len(), 2);
+        assert_eq!(leaves[1].1.name(), "LessThan");
+
+        assert!(!evaluation.reevaluate_if(&7, &[]));
+        assert_eq!(count(&greater_than_evaluations), 1);
+        assert_eq!(count(&less_than_evaluations), 1);
+
+        assert!(evaluation.reevaluate_if(&12, &[leaves[1].0]));
+        assert_eq!(count(&greater_than_evaluations), 1);
+        assert_eq!(count(&less_than_evaluations), 2);
+
+        assert!(evaluation.reevaluate_if(&12, &[]));
+        assert_eq!(count(&greater_than_evaluations), 1);
+        assert_eq!(count(&less_than_evaluations), 2);
+    }
+
+    #[test]
+    fn test_ttl_cached_before_expiry() {
+        let clock = FakeClock::new();
+        let (specification, evaluations) = counting(Zero {});
+        let cached = TtlCached::with_clock(specification, Duration::from_secs(60), clock.clone());
+
+        assert!(cached.is_satisfied_by(&0));
+        clock.advance(Duration::from_secs(59));
+        assert!(cached.is_satisfied_by(&0));
+
+        assert_eq!(count(&evaluations), 1);
+    }
+
+    #[test]
+    fn test_ttl_cached_after_expiry() {
+        let clock = FakeClock::new();
+        let (specification, evaluations) = counting(Zero {});
+        let cached = TtlCached::with_clock(specification, Duration::from_secs(60), clock.clone());
+
+        assert!(cached.is_satisfied_by(&0));
+        clock.advance(Duration::from_secs(60));
+        assert!(cached.is_satisfied_by(&0));
+        assert_eq!(count(&evaluations), 2);
+
+        clock.advance(Duration::from_secs(1));
+        assert!(cached.is_satisfied_by(&0));
+        assert_eq!(count(&evaluations), 2);
+    }
+    #[test]
+    fn test_ttl_cached_unlocked_while_evaluating() {
+        let (specification, started, release) = handshake();
+        let cached = TtlCached::new(specification, Duration::from_secs(60));
+
+        assert_unlocked_while_evaluating(&cached, &started, &release);
+    }
+}
@@ -0,0 +1,461 @@
+// Building compositions from many specifications at once.
+
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::{Specification, SpecificationCompositions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CombineOp {
+    And,
+    Or,
+    Xor,
+}
+
+/// The kind of a combinator, as in [`SpecificationCompositions::identity`].
+pub type CombineKind = CombineOp;
+
+/// An `And` or `Or` was about to be built without any children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyCompositionError;
+
+impl Display for EmptyCompositionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a composition needs at least one specification")
+    }
+}
+
+impl std::error::Error for EmptyCompositionError {}
+
+/// A row of a truth table, by index, doesn't have a value per leaf, see [`from_truth_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowLengthError {
+    pub row: usize,
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl Display for RowLengthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "row {} has {} values, expected {}, one per leaf",
+            self.row, self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for RowLengthError {}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// The identity element of the combinator, the result of combining no specifications:
+    /// `True` for `And`, `False` for `Or` and `Xor`. The right starting point of a fold.
+    pub fn identity(kind: CombineKind) -> Self {
+        match kind {
+            CombineKind::And => Self::True,
+            CombineKind::Or | CombineKind::Xor => Self::False,
+        }
+    }
+
+    /// A combinator of the chosen `kind` over the `specifications`, as they are,
+    /// for code generating rules that doesn't want to match on the kind itself.
+    pub fn combine(kind: CombineKind, specifications: Vec<Self>) -> Self {
+        match kind {
+            CombineKind::And => Self::And(specifications),
+            CombineKind::Or => Self::Or(specifications),
+            CombineKind::Xor => Self::Xor(specifications),
+        }
+    }
+
+    /// Unites two rule sets under the chosen connective, then [`flatten`](Self::flatten)s
+    /// and [`dedup`](Self::dedup)s the result, so the rules both sets share appear once,
+    /// or cancel out under `Xor`. Shared means equal as in `==`, distinct closure leaves
+    /// printing alike are both kept.
+    pub fn merge(self, other: Self, kind: CombineKind) -> Self {
+        Self::combine(kind, vec![self, other]).flatten().dedup()
+    }
+
+    /// An `And` of the `specifications`, rejecting an empty one that would be vacuously true.
+    pub fn try_and_of(
+        specifications: impl IntoIterator<Item = Self>,
+    ) -> Result<Self, EmptyCompositionError> {
+        let specifications: Vec<Self> = specifications.into_iter().collect();
+        if specifications.is_empty() {
+            return Err(EmptyCompositionError);
+        }
+        Ok(Self::And(specifications))
+    }
+
+    /// An `Or` of the `specifications`, rejecting an empty one that would never be satisfied.
+    pub fn try_any_of(
+        specifications: impl IntoIterator<Item = Self>,
+    ) -> Result<Self, EmptyCompositionError> {
+        let specifications: Vec<Self> = specifications.into_iter().collect();
+        if specifications.is_empty() {
+            return Err(EmptyCompositionError);
+        }
+        Ok(Self::Or(specifications))
+    }
+}
+
+/// Folds `specifications` with the chosen combinator, `None` if there are none.
+pub fn reduce<T: std::fmt::Debug + 'static>(
+    specifications: impl IntoIterator<Item = SpecificationCompositions<T>>,
+    op: CombineOp,
+) -> Option<SpecificationCompositions<T>> {
+    specifications
+        .into_iter()
+        .reduce(|combined, specification| match op {
+            CombineOp::And => combined.and(specification),
+            CombineOp::Or => combined.or(specification),
+            CombineOp::Xor => combined.xor(specification),
+        })
+}
+
+/// `a and b` for specifications only known as trait objects,
+/// which don't get the combinator methods as those need `Sized`.
+pub fn and_dyn<T: std::fmt::Debug>(
+    a: Arc<dyn Specification<T>>,
+    b: Arc<dyn Specification<T>>,
+) -> SpecificationCompositions<T> {
+    SpecificationCompositions::And(vec![
+        SpecificationCompositions::Specification(a),
+        SpecificationCompositions::Specification(b),
+    ])
+}
+
+/// `a or b` for specifications only known as trait objects, see [`and_dyn`].
+pub fn or_dyn<T: std::fmt::Debug>(
+    a: Arc<dyn Specification<T>>,
+    b: Arc<dyn Specification<T>>,
+) -> SpecificationCompositions<T> {
+    SpecificationCompositions::Or(vec![
+        SpecificationCompositions::Specification(a),
+        SpecificationCompositions::Specification(b),
+    ])
+}
+
+/// `not a` for a specification only known as a trait object, see [`and_dyn`].
+pub fn not_dyn<T: std::fmt::Debug>(a: Arc<dyn Specification<T>>) -> SpecificationCompositions<T> {
+    SpecificationCompositions::Invert(Box::new(SpecificationCompositions::Specification(a)))
+}
+
+/// Builds the disjunctive normal form of a truth table: every row evaluating to true becomes
+/// an `And` of the leaves, inverted where the row has them false, and the rows are `Or`ed.
+///
+/// Rows not evaluating to true are left out, a table without any true row gives `False`.
+/// Every row, true or not, must have one value per leaf.
+pub fn from_truth_table<T: std::fmt::Debug>(
+    leaves: Vec<Arc<dyn Specification<T>>>,
+    rows: &[(Vec<bool>, bool)],
+) -> Result<SpecificationCompositions<T>, RowLengthError> {
+    if let Some((row, (values, _))) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, (values, _))| values.len() != leaves.len())
+    {
+        return Err(RowLengthError {
+            row,
+            expected: leaves.len(),
+            found: values.len(),
+        });
+    }
+    let terms: Vec<SpecificationCompositions<T>> = rows
+        .iter()
+        .filter(|(_, result)| *result)
+        .map(|(values, _)| {
+            let literals = leaves
+                .iter()
+                .zip(values)
+                .map(|(leaf, value)| {
+                    let leaf = SpecificationCompositions::Specification(leaf.clone());
+                    if *value {
+                        leaf
+                    } else {
+                        SpecificationCompositions::Invert(Box::new(leaf))
+                    }
+                })
+                .collect();
+            SpecificationCompositions::And(literals)
+        })
+        .collect();
+    if terms.is_empty() {
+        Ok(SpecificationCompositions::False)
+    } else {
+        Ok(SpecificationCompositions::Or(terms))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    fn specifications() -> Vec<SpecificationCompositions<i32>> {
+        vec![
+            GreaterThan { value: 5 }.composite(),
+            LessThan { value: 10 }.composite(),
+            Zero {}.invert(),
+        ]
+    }
+
+    #[test]
+    fn test_combine() {
+        let dedicated = [
+            GreaterThan { value: 5 }
+                .and(LessThan { value: 10 })
+                .and(Zero {}.invert()),
+            GreaterThan { value: 5 }
+                .or(LessThan { value: 10 })
+                .or(Zero {}.invert()),
+            GreaterThan { value: 5 }
+                .xor(LessThan { value: 10 })
+                .xor(Zero {}.invert()),
+        ];
+        let kinds = [CombineKind::And, CombineKind::Or, CombineKind::Xor];
+
+        for (kind, dedicated) in kinds.into_iter().zip(dedicated) {
+            let combined = SpecificationCompositions::combine(kind, specifications());
+            assert_eq!(combined, dedicated);
+            for candidate in -5..15 {
+                assert_eq!(
+                    combined.is_satisfied_by(&candidate),
+                    dedicated.is_satisfied_by(&candidate),
+                    "{kind:?} candidate {candidate}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(Zero {}.invert());
+        let b = LessThan { value: 10 }
+            .and(GreaterThan { value: 0 })
+            .and(Zero {}.invert());
+
+        let merged = a.merge(b, CombineKind::And);
+
+        assert_eq!(
+            merged,
+            GreaterThan { value: 5 }
+                .and(LessThan { value: 10 })
+                .and(Zero {}.invert())
+                .and(GreaterThan { value: 0 })
+        );
+    }
+
+    #[test]
+    fn test_merge_or() {
+        let merged = GreaterThan { value: 5 }
+            .or(Zero {})
+            .merge(Zero {}.composite(), CombineKind::Or);
+
+        assert_eq!(merged, GreaterThan { value: 5 }.or(Zero {}));
+    }
+
+    #[test]
+    fn test_merge_closure_leaves() {
+        use crate::comparison::{fields_compare, CmpOp};
+
+        type Triple = (i32, i32, i32);
+        let first = fields_compare(|c: &Triple| c.0, |c: &Triple| c.1, CmpOp::Gt).composite();
+        let second = fields_compare(|c: &Triple| c.1, |c: &Triple| c.2, CmpOp::Gt).composite();
+
+        let merged = first.clone().merge(second, CombineKind::And);
+
+        assert_eq!(merged.children().len(), 2);
+        assert!(merged.is_satisfied_by(&(3, 2, 1)));
+        assert!(!merged.is_satisfied_by(&(3, 2, 5)));
+        assert_eq!(
+            first.clone().merge(first.clone(), CombineKind::And),
+            SpecificationCompositions::And(vec![first])
+        );
+    }
+
+    #[test]
+    fn test_reduce_and() {
+        let specification = reduce(specifications(), CombineOp::And).unwrap();
+
+        assert_eq!(
+            specification,
+            GreaterThan { value: 5 }
+                .and(LessThan { value: 10 })
+                .and(Zero {}.invert())
+        );
+    }
+
+    #[test]
+    fn test_reduce_or() {
+        let specification = reduce(specifications(), CombineOp::Or).unwrap();
+
+        assert_eq!(
+            specification,
+            GreaterThan { value: 5 }
+                .or(LessThan { value: 10 })
+                .or(Zero {}.invert())
+        );
+    }
+
+    #[test]
+    fn test_reduce_xor() {
+        let specification = reduce(specifications(), CombineOp::Xor).unwrap();
+
+        assert_eq!(
+            specification,
+            GreaterThan { value: 5 }
+                .xor(LessThan { value: 10 })
+                .xor(Zero {}.invert())
+        );
+        assert!(specification.is_satisfied_by(&6));
+        assert!(!specification.is_satisfied_by(&3));
+    }
+
+    #[test]
+    fn test_reduce_single() {
+        let specification = reduce(vec![Zero {}.composite()], CombineOp::And).unwrap();
+
+        assert_eq!(specification, Zero {}.composite());
+    }
+
+    #[test]
+    fn test_reduce_empty() {
+        assert!(reduce(Vec::<SpecificationCompositions<i32>>::new(), CombineOp::And).is_none());
+        assert!(reduce(Vec::<SpecificationCompositions<i32>>::new(), CombineOp::Or).is_none());
+    }
+
+    #[test]
+    fn test_identity_folds_empty() {
+        let empty = Vec::<SpecificationCompositions<i32>>::new;
+
+        let all = empty().into_iter().fold(
+            SpecificationCompositions::identity(CombineKind::And),
+            |a, b| a.and(b),
+        );
+        let any = empty().into_iter().fold(
+            SpecificationCompositions::identity(CombineKind::Or),
+            |a, b| a.or(b),
+        );
+
+        assert!(all.is_satisfied_by(&3));
+        assert!(!any.is_satisfied_by(&3));
+    }
+
+    #[test]
+    fn test_identity_is_neutral() {
+        for op in [CombineOp::And, CombineOp::Or, CombineOp::Xor] {
+            let identity = SpecificationCompositions::identity(op);
+            let specification = reduce([identity, Zero {}.composite()], op).unwrap();
+
+            assert!(specification.is_satisfied_by(&0));
+            assert!(!specification.is_satisfied_by(&1));
+        }
+    }
+
+    #[test]
+    fn test_try_and_of() {
+        let specification = SpecificationCompositions::try_and_of(specifications()).unwrap();
+
+        assert_eq!(
+            specification,
+            SpecificationCompositions::And(specifications())
+        );
+        assert_eq!(
+            SpecificationCompositions::<i32>::try_and_of(vec![]),
+            Err(EmptyCompositionError)
+        );
+    }
+
+    #[test]
+    fn test_try_any_of() {
+        let specification = SpecificationCompositions::try_any_of(specifications()).unwrap();
+
+        assert_eq!(
+            specification,
+            SpecificationCompositions::Or(specifications())
+        );
+        assert_eq!(
+            SpecificationCompositions::<i32>::try_any_of(vec![]),
+            Err(EmptyCompositionError)
+        );
+    }
+
+    fn boxed_leaves() -> Vec<Arc<dyn Specification<i32>>> {
+        vec![
+            Arc::new(GreaterThan { value: 5 }),
+            Arc::new(LessThan { value: 10 }),
+        ]
+    }
+
+    #[test]
+    fn test_and_or_not_dyn() {
+        let [a, b]: [Arc<dyn Specification<i32>>; 2] = boxed_leaves().try_into().unwrap();
+
+        let and = and_dyn(a.clone(), b.clone());
+        let or = or_dyn(a.clone(), b);
+        let not = not_dyn(a);
+
+        assert!(and.is_satisfied_by(&7));
+        assert!(!and.is_satisfied_by(&12));
+        assert!(or.is_satisfied_by(&12));
+        assert!(or.is_satisfied_by(&3));
+        assert!(not.is_satisfied_by(&3));
+        assert!(!not.is_satisfied_by(&7));
+        assert!(not.or(and).is_satisfied_by(&7));
+    }
+
+    #[test]
+    fn test_from_truth_table_xor() {
+        let leaves: Vec<Arc<dyn Specification<i32>>> = vec![
+            Arc::new(GreaterThan { value: 5 }),
+            Arc::new(LessThan { value: 10 }),
+        ];
+        let rows = [
+            (vec![false, false], false),
+            (vec![false, true], true),
+            (vec![true, false], true),
+            (vec![true, true], false),
+        ];
+
+        let specification = from_truth_table(leaves, &rows).unwrap();
+
+        assert_eq!(
+            specification.to_string(),
+            "((not GreaterThan { value: 5 } and LessThan { value: 10 }) or (GreaterThan { value: 5 } and not LessThan { value: 10 }))"
+        );
+        let xor = GreaterThan { value: 5 }.xor(LessThan { value: 10 });
+        for candidate in 0..15 {
+            assert_eq!(
+                specification.is_satisfied_by(&candidate),
+                xor.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_truth_table_without_true_rows() {
+        let leaves: Vec<Arc<dyn Specification<i32>>> = vec![Arc::new(Zero {})];
+
+        let specification = from_truth_table(leaves, &[(vec![true], false)]).unwrap();
+
+        assert_eq!(specification, SpecificationCompositions::False);
+    }
+
+    #[test]
+    fn test_from_truth_table_row_length() {
+        let leaves: Vec<Arc<dyn Specification<i32>>> =
+            vec![Arc::new(Zero {}), Arc::new(GreaterThan { value: 5 })];
+        let rows = [(vec![true, false], true), (vec![true], false)];
+
+        assert_eq!(
+            from_truth_table(leaves, &rows),
+            Err(RowLengthError {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+}
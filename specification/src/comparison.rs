@@ -0,0 +1,486 @@
+// Comparison leaves, over ordered values with a simplifier merging their bounds,
+// over values that can only be told equal or looked up in a set,
+// over numbers within a tolerance, and between two fields of the same candidate.
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{Specification, SpecificationCompositions};
+
+/// Satisfied by values strictly greater than `value`.
+pub fn gt<V>(value: V) -> Interval<V> {
+    Interval::new(Bound::Excluded(value), Bound::Unbounded)
+}
+
+/// Satisfied by values greater than or equal to `value`.
+pub fn ge<V>(value: V) -> Interval<V> {
+    Interval::new(Bound::Included(value), Bound::Unbounded)
+}
+
+/// Satisfied by values strictly less than `value`.
+pub fn lt<V>(value: V) -> Interval<V> {
+    Interval::new(Bound::Unbounded, Bound::Excluded(value))
+}
+
+/// Satisfied by values less than or equal to `value`.
+pub fn le<V>(value: V) -> Interval<V> {
+    Interval::new(Bound::Unbounded, Bound::Included(value))
+}
+
+/// Satisfied by values from `lower` to `upper`, both included.
+pub fn between<V>(lower: V, upper: V) -> Interval<V> {
+    Interval::new(Bound::Included(lower), Bound::Included(upper))
+}
+
+/// Satisfied by values equal to one of `values`, never when there are none.
+/// Needs only `PartialEq`, each check scans the values one by one, in linear time.
+pub fn one_of<V>(values: Vec<V>) -> OneOf<V> {
+    OneOf { values }
+}
+
+/// Satisfied by values at most `tolerance` away from `value`, both bounds included,
+/// for the floating-point fields where an exact comparison is brittle.
+pub fn approx_eq<V>(value: V, tolerance: f64) -> Approx<V> {
+    Approx {
+        value,
+        tolerance,
+        equal: true,
+    }
+}
+
+/// Satisfied by values more than `tolerance` away from `value`, the opposite of [`approx_eq`].
+pub fn approx_ne<V>(value: V, tolerance: f64) -> Approx<V> {
+    Approx {
+        value,
+        tolerance,
+        equal: false,
+    }
+}
+
+/// Satisfied by candidates whose `left` value compares to their `right` value as `op` says,
+/// for rules within a single record like "more contributions than years of experience".
+pub fn fields_compare<T, V, L: Fn(&T) -> V, R: Fn(&T) -> V>(
+    left: L,
+    right: R,
+    op: CmpOp,
+) -> FieldsCompare<L, R> {
+    FieldsCompare { left, right, op }
+}
+
+/// How [`fields_compare`] compares the left value to the right one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    pub fn compare<V: PartialOrd>(self, left: &V, right: &V) -> bool {
+        match self {
+            Self::Lt => left < right,
+            Self::Le => left <= right,
+            Self::Gt => left > right,
+            Self::Ge => left >= right,
+            Self::Eq => left == right,
+            Self::Ne => left != right,
+        }
+    }
+}
+
+pub struct FieldsCompare<L, R> {
+    left: L,
+    right: R,
+    op: CmpOp,
+}
+
+impl<L, R> Debug for FieldsCompare<L, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldsCompare")
+            .field("op", &self.op)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug, V: PartialOrd, L: Fn(&T) -> V, R: Fn(&T) -> V> Specification<T>
+    for FieldsCompare<L, R>
+{
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.op
+            .compare(&(self.left)(candidate), &(self.right)(candidate))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Approx<V> {
+    value: V,
+    tolerance: f64,
+    equal: bool,
+}
+
+impl<V: Debug + Copy + Into<f64>> Specification<V> for Approx<V> {
+    fn is_satisfied_by(&self, candidate: &V) -> bool {
+        let distance = ((*candidate).into() - self.value.into()).abs();
+        (distance <= self.tolerance) == self.equal
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+/// Satisfied only by the listed values, never when there are none.
+/// Unlike [`one_of`] the values are hashed, each check takes constant time.
+pub fn allow_list<V: Eq + Hash>(items: impl IntoIterator<Item = V>) -> Listed<V> {
+    Listed {
+        items: items.into_iter().collect(),
+        allow: true,
+    }
+}
+
+/// Satisfied by every value but the listed ones, always when there are none.
+pub fn deny_list<V: Eq + Hash>(items: impl IntoIterator<Item = V>) -> Listed<V> {
+    Listed {
+        items: items.into_iter().collect(),
+        allow: false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listed<V: Eq + Hash> {
+    items: HashSet<V>,
+    allow: bool,
+}
+
+impl<V: Debug + Eq + Hash> Specification<V> for Listed<V> {
+    fn is_satisfied_by(&self, candidate: &V) -> bool {
+        self.items.contains(candidate) == self.allow
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OneOf<V> {
+    values: Vec<V>,
+}
+
+impl<V: Debug + PartialEq> Specification<V> for OneOf<V> {
+    fn is_satisfied_by(&self, candidate: &V) -> bool {
+        self.values.contains(candidate)
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+/// A range of values between two bounds, each of them included, excluded or unbounded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval<V> {
+    lower: Bound<V>,
+    upper: Bound<V>,
+}
+
+impl<V> Interval<V> {
+    pub fn new(lower: Bound<V>, upper: Bound<V>) -> Self {
+        Self { lower, upper }
+    }
+}
+
+impl<V: PartialOrd + Clone> Interval<V> {
+    /// The values in both intervals, `None` when there are none.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let lower = match (&self.lower, &other.lower) {
+            (Bound::Unbounded, bound) | (bound, Bound::Unbounded) => bound.clone(),
+            (a, b) if bound_value(a) > bound_value(b) => a.clone(),
+            (a, b) if bound_value(a) < bound_value(b) => b.clone(),
+            (Bound::Excluded(value), _) | (_, Bound::Excluded(value)) => {
+                Bound::Excluded(value.clone())
+            }
+            (bound, _) => bound.clone(),
+        };
+        let upper = match (&self.upper, &other.upper) {
+            (Bound::Unbounded, bound) | (bound, Bound::Unbounded) => bound.clone(),
+            (a, b) if bound_value(a) < bound_value(b) => a.clone(),
+            (a, b) if bound_value(a) > bound_value(b) => b.clone(),
+            (Bound::Excluded(value), _) | (_, Bound::Excluded(value)) => {
+                Bound::Excluded(value.clone())
+            }
+            (bound, _) => bound.clone(),
+        };
+        let empty = match (&lower, &upper) {
+            (Bound::Included(lower), Bound::Included(upper)) => lower > upper,
+            (
+                Bound::Included(lower) | Bound::Excluded(lower),
+                Bound::Included(upper) | Bound::Excluded(upper),
+            ) => lower >= upper,
+            _ => false,
+        };
+        (!empty).then_some(Self { lower, upper })
+    }
+}
+
+fn bound_value<V>(bound: &Bound<V>) -> Option<&V> {
+    match bound {
+        Bound::Included(value) | Bound::Excluded(value) => Some(value),
+        Bound::Unbounded => None,
+    }
+}
+
+impl<V: Debug + PartialOrd + 'static> Specification<V> for Interval<V> {
+    fn is_satisfied_by(&self, candidate: &V) -> bool {
+        (self.lower.as_ref(), self.upper.as_ref()).contains(candidate)
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+}
+
+impl<T: Debug + PartialOrd + Clone + 'static> SpecificationCompositions<T> {
+    /// Merges the [`Interval`] leaves directly under the same `And` into their intersection,
+    /// so `gt(5).and(gt(3))` becomes `gt(5)` and `gt(5).and(lt(10))` a single range.
+    /// An `And` of disjoint intervals can't be satisfied, it becomes `False`.
+    ///
+    /// The leaves are recognized by downcasting, other leaves are kept as they are.
+    pub fn simplify_numeric(self) -> Self {
+        match self {
+            Self::And(specifications) => {
+                let mut merged: Option<(usize, Interval<T>)> = None;
+                let mut simplified = Vec::new();
+                for specification in specifications.into_iter().map(Self::simplify_numeric) {
+                    let interval = match &specification {
+                        Self::Specification(f) => f
+                            .as_any()
+                            .and_then(|any| any.downcast_ref::<Interval<T>>())
+                            .cloned(),
+                        _ => None,
+                    };
+                    match (interval, &mut merged) {
+                        (Some(interval), Some((_, merged))) => match merged.intersect(&interval) {
+                            Some(intersection) => *merged = intersection,
+                            None => return Self::False,
+                        },
+                        (Some(interval), None) => {
+                            merged = Some((simplified.len(), interval));
+                            simplified.push(specification);
+                        }
+                        (None, _) => simplified.push(specification),
+                    }
+                }
+                if let Some((position, interval)) = merged {
+                    simplified[position] = interval.composite();
+                }
+                if simplified.len() == 1 {
+                    return simplified.remove(0);
+                }
+                Self::And(simplified)
+            }
+            Self::Or(specifications) => Self::Or(
+                specifications
+                    .into_iter()
+                    .map(Self::simplify_numeric)
+                    .collect(),
+            ),
+            Self::Xor(specifications) => Self::Xor(
+                specifications
+                    .into_iter()
+                    .map(Self::simplify_numeric)
+                    .collect(),
+            ),
+            Self::Invert(specification) => Self::Invert(Box::new(specification.simplify_numeric())),
+            Self::WeightedAtLeast(threshold, specifications) => Self::WeightedAtLeast(
+                threshold,
+                specifications
+                    .into_iter()
+                    .map(|(weight, specification)| (weight, specification.simplify_numeric()))
+                    .collect(),
+            ),
+            Self::MinSatisfied(count, specifications) => Self::MinSatisfied(
+                count,
+                specifications
+                    .into_iter()
+                    .map(Self::simplify_numeric)
+                    .collect(),
+            ),
+            Self::MaxSatisfied(count, specifications) => Self::MaxSatisfied(
+                count,
+                specifications
+                    .into_iter()
+                    .map(Self::simplify_numeric)
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_interval() {
+        let specification = gt(5).and(le(10)).or(between(20, 30));
+
+        assert!(!specification.is_satisfied_by(&5));
+        assert!(specification.is_satisfied_by(&10));
+        assert!(!specification.is_satisfied_by(&11));
+        assert!(specification.is_satisfied_by(&20));
+        assert!(specification.is_satisfied_by(&30));
+        assert!(ge(5).is_satisfied_by(&5));
+        assert!(!lt(5).is_satisfied_by(&5));
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Language {
+        Rust,
+        Python,
+        Go,
+    }
+
+    #[test]
+    fn test_one_of() {
+        let specification = one_of(vec![Language::Rust, Language::Go]);
+
+        assert!(specification.is_satisfied_by(&Language::Rust));
+        assert!(specification.is_satisfied_by(&Language::Go));
+        assert!(!specification.is_satisfied_by(&Language::Python));
+    }
+
+    #[test]
+    fn test_one_of_empty() {
+        let specification = one_of(vec![]);
+
+        assert!(!specification.is_satisfied_by(&Language::Rust));
+        assert!(!specification.is_satisfied_by(&Language::Python));
+    }
+
+    #[test]
+    fn test_allow_list() {
+        let specification = allow_list(["John".to_string(), "Mike".to_string()]);
+
+        assert!(specification.is_satisfied_by(&"John".to_string()));
+        assert!(specification.is_satisfied_by(&"Mike".to_string()));
+        assert!(!specification.is_satisfied_by(&"Anna".to_string()));
+    }
+
+    #[test]
+    fn test_deny_list() {
+        let specification = deny_list(["John".to_string(), "Mike".to_string()]);
+
+        assert!(!specification.is_satisfied_by(&"John".to_string()));
+        assert!(!specification.is_satisfied_by(&"Mike".to_string()));
+        assert!(specification.is_satisfied_by(&"Anna".to_string()));
+    }
+
+    #[test]
+    fn test_lists_empty() {
+        assert!(!allow_list(Vec::<i32>::new()).is_satisfied_by(&1));
+        assert!(deny_list(Vec::<i32>::new()).is_satisfied_by(&1));
+    }
+
+    #[test]
+    fn test_lists_composed() {
+        let specification = allow_list(1..=20)
+            .and(deny_list([7, 13]))
+            .and(GreaterThan { value: 5 });
+
+        assert!(specification.is_satisfied_by(&6));
+        assert!(!specification.is_satisfied_by(&7));
+        assert!(!specification.is_satisfied_by(&3));
+        assert!(!specification.is_satisfied_by(&21));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let specification = approx_eq(10.0, 0.5);
+
+        assert!(specification.is_satisfied_by(&10.0));
+        assert!(specification.is_satisfied_by(&9.5));
+        assert!(specification.is_satisfied_by(&10.5));
+        assert!(specification.is_satisfied_by(&10.499));
+        assert!(!specification.is_satisfied_by(&10.501));
+        assert!(!specification.is_satisfied_by(&9.499));
+        assert!(!specification.is_satisfied_by(&f64::NAN));
+    }
+
+    #[test]
+    fn test_approx_eq_integers() {
+        let specification = approx_eq(5_i32, 1.0);
+
+        assert!(specification.is_satisfied_by(&4));
+        assert!(specification.is_satisfied_by(&6));
+        assert!(!specification.is_satisfied_by(&7));
+    }
+
+    #[test]
+    fn test_approx_ne() {
+        let specification = approx_ne(0.1 + 0.2, 1e-9);
+
+        assert!(!specification.is_satisfied_by(&0.3));
+        assert!(specification.is_satisfied_by(&0.300_001));
+        assert!(specification.is_satisfied_by(&0.299_999));
+        assert!(specification.is_satisfied_by(&f64::NAN));
+    }
+
+    #[test]
+    fn test_cmp_op() {
+        assert!(CmpOp::Lt.compare(&1, &2));
+        assert!(!CmpOp::Lt.compare(&2, &2));
+        assert!(CmpOp::Le.compare(&2, &2));
+        assert!(CmpOp::Gt.compare(&3, &2));
+        assert!(!CmpOp::Gt.compare(&2, &2));
+        assert!(CmpOp::Ge.compare(&2, &2));
+        assert!(CmpOp::Eq.compare(&2, &2));
+        assert!(CmpOp::Ne.compare(&1, &2));
+        assert!(!CmpOp::Ne.compare(&2, &2));
+    }
+
+    #[test]
+    fn test_simplify_numeric_redundant_bound() {
+        assert_eq!(gt(5).and(gt(3)).simplify_numeric(), gt(5).composite());
+        assert_eq!(gt(5).and(ge(5)).simplify_numeric(), gt(5).composite());
+    }
+
+    #[test]
+    fn test_simplify_numeric_range() {
+        let specification = gt(5).and(Zero {}.invert()).and(lt(10)).and(le(12));
+
+        assert_eq!(
+            specification.simplify_numeric(),
+            Interval::new(Bound::Excluded(5), Bound::Excluded(10)).and(Zero {}.invert())
+        );
+    }
+
+    #[test]
+    fn test_simplify_numeric_contradiction() {
+        assert_eq!(
+            gt(10).and(lt(5)).simplify_numeric(),
+            SpecificationCompositions::False
+        );
+        assert_eq!(
+            ge(5).and(lt(5)).simplify_numeric(),
+            SpecificationCompositions::False
+        );
+        assert_eq!(
+            between(5, 5).and(le(5)).simplify_numeric(),
+            between(5, 5).composite()
+        );
+    }
+
+    #[test]
+    fn test_simplify_numeric_nested() {
+        let specification = Zero {}.or(gt(1).and(gt(2)).invert());
+
+        assert_eq!(specification.simplify_numeric(), Zero {}.or(gt(2).invert()));
+    }
+}
@@ -0,0 +1,344 @@
+// Helpers to evaluate a specification against a whole dataset at once.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{Specification, SpecificationCompositions};
+
+/// Splits `items` into the ones satisfying `specification` and the ones that don't,
+/// in a single pass, preserving the input order within each group.
+pub fn group_by_satisfaction<'a, T: std::fmt::Debug>(
+    specification: &impl Specification<T>,
+    items: &'a [T],
+) -> (Vec<&'a T>, Vec<&'a T>) {
+    specification.partition_ref(items)
+}
+
+/// How a dataset did against a specification, see [`SpecificationCompositions::dataset_stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetStats {
+    pub total: usize,
+    pub satisfied: usize,
+    /// The satisfied share of the items, from 0 to 1, and 0 for no items.
+    pub satisfaction_rate: f64,
+    /// The same as [`SpecificationCompositions::failure_histogram`].
+    pub failure_histogram: HashMap<String, usize>,
+}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Counts, per leaf [`Specification::name`], how many of the `items` were rejected by it.
+    ///
+    /// Only the leaves in the unsatisfied remainder of a failing candidate count, or of the
+    /// failing node itself, like an `Invert`, where the remainder can't tell.
+    /// Each leaf name counts at most once per candidate.
+    pub fn failure_histogram(&self, items: &[T]) -> HashMap<String, usize> {
+        self.dataset_stats(items).failure_histogram
+    }
+
+    /// Everything about how the `items` did, evaluating each of them once.
+    pub fn dataset_stats(&self, items: &[T]) -> DatasetStats {
+        let mut satisfied = 0;
+        let mut histogram = HashMap::new();
+        for item in items {
+            match self.failure_reminder(item) {
+                None => satisfied += 1,
+                Some(reminder) => {
+                    let mut names = HashSet::new();
+                    reminder.for_each_leaf(&mut |leaf| {
+                        names.insert(leaf.name());
+                    });
+                    for name in names {
+                        *histogram.entry(name).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        DatasetStats {
+            total: items.len(),
+            satisfied,
+            satisfaction_rate: if items.is_empty() {
+                0.0
+            } else {
+                satisfied as f64 / items.len() as f64
+            },
+            failure_histogram: histogram,
+        }
+    }
+
+    /// How many of the `items` each direct child of the root satisfies, in the order of the
+    /// children, to spot the criterion holding a cohort back. Empty for a leaf root.
+    pub fn per_child_counts(&self, items: &[T]) -> Vec<usize> {
+        self.children()
+            .into_iter()
+            .map(|child| {
+                items
+                    .iter()
+                    .filter(|item| child.is_satisfied_by(item))
+                    .count()
+            })
+            .collect()
+    }
+
+    /// Lazily pairs every item with its result, each item is evaluated as the iterator
+    /// reaches it, so nothing is allocated and unconsumed items are never evaluated.
+    pub fn evaluate_all<'a>(&'a self, items: &'a [T]) -> impl Iterator<Item = (&'a T, bool)> + 'a {
+        items.iter().map(|item| (item, self.is_satisfied_by(item)))
+    }
+
+    /// Lazily pairs every failing item with its unsatisfied remainder, skipping the ones that
+    /// pass, the streaming version of a rejection report. Where the remainder can't tell,
+    /// like for a failing `Invert`, the failing node itself is reported.
+    pub fn reminders_for<'a>(&'a self, items: &'a [T]) -> impl Iterator<Item = (&'a T, Self)> + 'a {
+        items
+            .iter()
+            .filter_map(|item| self.failure_reminder(item).map(|reminder| (item, reminder)))
+    }
+
+    /// Pairs every item with its unsatisfied remainder, `None` for the ones that pass,
+    /// to report in one go who passed and why the others didn't. Where the remainder
+    /// can't tell, like for a failing `Invert`, the failing node itself is the reason.
+    pub fn filtered_with_reasons<'a>(&self, items: &'a [T]) -> Vec<(&'a T, Option<Self>)> {
+        items
+            .iter()
+            .map(|item| (item, self.failure_reminder(item)))
+            .collect()
+    }
+
+    /// Evaluates the composition against every item, returning one result per item.
+    ///
+    /// The tree is traversed once for the whole batch: every leaf is evaluated into a mask
+    /// and the masks are combined element-wise by the combinators. Unlike `is_satisfied_by`
+    /// this doesn't short-circuit, every leaf runs for every item.
+    pub fn evaluate_mask(&self, items: &[T]) -> Vec<bool> {
+        match self {
+            Self::Specification(f) => items.iter().map(|item| f.is_satisfied_by(item)).collect(),
+            Self::And(specifications) => {
+                Self::combine_masks(specifications, items, true, |mask, child| *mask &= child)
+            }
+            Self::Or(specifications) => {
+                Self::combine_masks(specifications, items, false, |mask, child| *mask |= child)
+            }
+            Self::Xor(specifications) => Self::count_masks(specifications, items)
+                .into_iter()
+                .map(|count| count % 2 == 1)
+                .collect(),
+            Self::Invert(specification) => specification
+                .evaluate_mask(items)
+                .into_iter()
+                .map(|satisfied| !satisfied)
+                .collect(),
+            Self::True => vec![true; items.len()],
+            Self::False => vec![false; items.len()],
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let mut weights = vec![0.0; items.len()];
+                for (weight, specification) in specifications {
+                    for (sum, satisfied) in
+                        weights.iter_mut().zip(specification.evaluate_mask(items))
+                    {
+                        if satisfied {
+                            *sum += weight;
+                        }
+                    }
+                }
+                weights.into_iter().map(|sum| sum >= *threshold).collect()
+            }
+            Self::MinSatisfied(minimum, specifications) => Self::count_masks(specifications, items)
+                .into_iter()
+                .map(|count| count >= *minimum)
+                .collect(),
+            Self::MaxSatisfied(maximum, specifications) => Self::count_masks(specifications, items)
+                .into_iter()
+                .map(|count| count <= *maximum)
+                .collect(),
+        }
+    }
+
+    fn combine_masks(
+        specifications: &[Self],
+        items: &[T],
+        identity: bool,
+        combine: impl Fn(&mut bool, bool),
+    ) -> Vec<bool> {
+        let mut mask = vec![identity; items.len()];
+        for specification in specifications {
+            for (value, satisfied) in mask.iter_mut().zip(specification.evaluate_mask(items)) {
+                combine(value, satisfied);
+            }
+        }
+        mask
+    }
+
+    // How many of the children each item satisfies.
+    fn count_masks(specifications: &[Self], items: &[T]) -> Vec<usize> {
+        let mut counts = vec![0; items.len()];
+        for specification in specifications {
+            for (count, satisfied) in counts.iter_mut().zip(specification.evaluate_mask(items)) {
+                *count += usize::from(satisfied);
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_group_by_satisfaction() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 });
+        let items = [3, 7, 12, 6, 0, 9];
+
+        let (satisfied, unsatisfied) = group_by_satisfaction(&specification, &items);
+
+        assert_eq!(satisfied, vec![&7, &6, &9]);
+        assert_eq!(unsatisfied, vec![&3, &12, &0]);
+    }
+
+    #[test]
+    fn test_evaluate_mask() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .or(Zero {})
+            .or(GreaterThan { value: 20 }
+                .xor(LessThan { value: 30 })
+                .invert());
+        let items: Vec<i32> = (-10..40).collect();
+
+        let mask = specification.evaluate_mask(&items);
+
+        let expected: Vec<bool> = items
+            .iter()
+            .map(|item| specification.is_satisfied_by(item))
+            .collect();
+        assert_eq!(mask, expected);
+    }
+
+    #[test]
+    fn test_evaluate_all() {
+        let (greater_than, evaluations) = counting(GreaterThan { value: 5 });
+        let specification = greater_than.composite();
+        let items = [3, 7, 12];
+
+        let mut results = specification.evaluate_all(&items);
+        assert_eq!(count(&evaluations), 0);
+
+        assert_eq!(results.next(), Some((&3, false)));
+        assert_eq!(count(&evaluations), 1);
+        assert_eq!(results.collect::<Vec<_>>(), vec![(&7, true), (&12, true)]);
+        assert_eq!(count(&evaluations), 3);
+    }
+
+    #[test]
+    fn test_reminders_for() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 });
+        let items = [3, 7, 12, 6, 9];
+
+        let reminders: Vec<(&i32, SpecificationCompositions<i32>)> =
+            specification.reminders_for(&items).collect();
+
+        assert_eq!(
+            reminders,
+            vec![
+                (&3, GreaterThan { value: 5 }.composite()),
+                (&12, LessThan { value: 10 }.composite()),
+            ]
+        );
+        assert_eq!(specification.reminders_for(&[7, 8]).count(), 0);
+    }
+
+    #[test]
+    fn test_reminders_for_inverted() {
+        let specification = LessThan { value: 10 }.and(Zero {}.invert());
+        let items = [0, 5, 12];
+
+        let reminders: Vec<(&i32, SpecificationCompositions<i32>)> =
+            specification.reminders_for(&items).collect();
+
+        assert_eq!(
+            reminders,
+            vec![
+                (&0, Zero {}.invert()),
+                (&12, LessThan { value: 10 }.composite()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filtered_with_reasons_inverted_and_false() {
+        let specification = LessThan { value: 10 }.and(Zero {}.invert());
+        let never = SpecificationCompositions::And(vec![
+            LessThan { value: 10 }.composite(),
+            SpecificationCompositions::False,
+        ]);
+
+        assert_eq!(
+            specification.filtered_with_reasons(&[0, 5]),
+            vec![(&0, Some(Zero {}.invert())), (&5, None)]
+        );
+        assert_eq!(
+            never.filtered_with_reasons(&[5]),
+            vec![(&5, Some(SpecificationCompositions::False))]
+        );
+    }
+
+    #[test]
+    fn test_per_child_counts() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(Zero {}.invert());
+        let items = [3, 7, 12, 6, 0, 9, 15];
+
+        assert_eq!(specification.per_child_counts(&items), vec![5, 5, 6]);
+        assert!(Zero {}.composite().per_child_counts(&items).is_empty());
+    }
+
+    #[test]
+    fn test_dataset_stats_empty() {
+        let stats = Zero {}.composite().dataset_stats(&[]);
+
+        assert_eq!(stats.total, 0);
+        assert_eq!(stats.satisfied, 0);
+        assert_eq!(stats.satisfaction_rate, 0.0);
+        assert!(stats.failure_histogram.is_empty());
+    }
+
+    #[test]
+    fn test_dataset_stats_inverted() {
+        let specification = LessThan { value: 10 }.and(Zero {}.invert());
+
+        let stats = specification.dataset_stats(&[0, 5]);
+
+        assert_eq!(stats.satisfied, 1);
+        assert_eq!(stats.satisfaction_rate, 0.5);
+        assert_eq!(
+            stats.failure_histogram,
+            HashMap::from([("Zero".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn test_dataset_stats_false() {
+        let specification = SpecificationCompositions::And(vec![
+            LessThan { value: 10 }.composite(),
+            SpecificationCompositions::False,
+        ]);
+
+        let stats = specification.dataset_stats(&[0, 5]);
+
+        assert_eq!(stats.satisfied, 0);
+        assert!(stats.failure_histogram.is_empty());
+    }
+
+    #[test]
+    fn test_failure_histogram() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 });
+        let items = [3, 7, 12, 6, 0, 9, 15];
+
+        let histogram = specification.failure_histogram(&items);
+
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(histogram["GreaterThan"], 2);
+        assert_eq!(histogram["LessThan"], 2);
+    }
+}
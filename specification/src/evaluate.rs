@@ -0,0 +1,655 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::{Specification, SpecificationCompositions};
+
+type Predicate<'a, T> = Box<dyn Fn(&T) -> bool + 'a>;
+
+/// Counters of a single evaluation, see [`SpecificationCompositions::evaluate_with_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvalStats {
+    /// Leaves actually evaluated, the ones skipped by short-circuiting aren't counted.
+    pub leaves: usize,
+    /// Combinator nodes visited, `Invert` included.
+    pub combinators: usize,
+}
+
+/// How [`SpecificationCompositions::is_satisfied_by_with`] evaluates `And` and `Or`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvalPolicy {
+    /// Stops at the first child deciding the result, like `is_satisfied_by`.
+    #[default]
+    ShortCircuit,
+    /// Evaluates every leaf, for their side effects or a full explanation.
+    Exhaustive,
+}
+
+/// The evaluation reached nodes nested deeper than allowed,
+/// see [`SpecificationCompositions::is_satisfied_by_limited`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthExceeded {
+    pub max_depth: usize,
+}
+
+impl Display for DepthExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the composition is nested deeper than {}",
+            self.max_depth
+        )
+    }
+}
+
+impl std::error::Error for DepthExceeded {}
+
+/// Evaluates batches of candidates against a composition, keeping the stack of the
+/// evaluation between candidates and between batches instead of allocating it every time,
+/// see [`SpecificationCompositions::batch_evaluator`].
+#[derive(Debug)]
+pub struct BatchEvaluator<'a, T: std::fmt::Debug> {
+    specification: &'a SpecificationCompositions<T>,
+    stack: Vec<Frame<'a, T>>,
+}
+
+impl<'a, T: std::fmt::Debug> BatchEvaluator<'a, T> {
+    /// Clears `out` and fills it with the result for every item, in the order of `items`.
+    pub fn evaluate_batch(&mut self, items: &[T], out: &mut Vec<bool>) {
+        out.clear();
+        out.reserve(items.len());
+        for item in items {
+            let satisfied = self
+                .specification
+                .evaluate_on_stack(&mut self.stack, None, &mut |f| f.is_satisfied_by(item))
+                .unwrap_or_default();
+            out.push(satisfied);
+        }
+    }
+}
+
+// A node being evaluated by `evaluate_iteratively`, with the results of its children so far.
+#[derive(Debug)]
+struct Frame<'a, T: std::fmt::Debug> {
+    node: &'a SpecificationCompositions<T>,
+    next: usize,
+    satisfied: usize,
+    weight: f64,
+}
+
+impl<'a, T: std::fmt::Debug> Frame<'a, T> {
+    fn new(node: &'a SpecificationCompositions<T>) -> Self {
+        Self {
+            node,
+            next: 0,
+            satisfied: 0,
+            weight: 0.0,
+        }
+    }
+
+    fn record(&mut self, satisfied: bool) {
+        if satisfied {
+            self.satisfied += 1;
+            if let SpecificationCompositions::WeightedAtLeast(_, specifications) = self.node {
+                self.weight += specifications[self.next - 1].0;
+            }
+        }
+    }
+
+    // The result of the node once the children seen so far decide it, short-circuiting
+    // `And` and `Or`. Xor holds when an odd number of its children hold, so it stays
+    // associative.
+    fn decided(&self, leaf: &mut impl FnMut(&Arc<dyn Specification<T>>) -> bool) -> Option<bool> {
+        let done = self.next == self.node.child_count();
+        match self.node {
+            SpecificationCompositions::Specification(f) => Some(leaf(f)),
+            SpecificationCompositions::True => Some(true),
+            SpecificationCompositions::False => Some(false),
+            SpecificationCompositions::And(..) if self.satisfied < self.next => Some(false),
+            SpecificationCompositions::Or(..) if self.satisfied > 0 => Some(true),
+            _ if !done => None,
+            SpecificationCompositions::And(..) => Some(true),
+            SpecificationCompositions::Or(..) => Some(false),
+            SpecificationCompositions::Xor(..) => Some(self.satisfied % 2 == 1),
+            SpecificationCompositions::Invert(..) => Some(self.satisfied == 0),
+            SpecificationCompositions::WeightedAtLeast(threshold, _) => {
+                Some(self.weight >= *threshold)
+            }
+            SpecificationCompositions::MinSatisfied(count, _) => Some(self.satisfied >= *count),
+            SpecificationCompositions::MaxSatisfied(count, _) => Some(self.satisfied <= *count),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Evaluates like `is_satisfied_by`, but fails instead of descending more than `max_depth`
+    /// nodes deep, the root being at depth 1, to guard against pathological generated trees.
+    /// Only the nodes actually evaluated count, a deep branch skipped by short-circuiting doesn't.
+    ///
+    /// The tree is walked with an explicit stack, so even the depth itself can't overflow.
+    pub fn is_satisfied_by_limited(
+        &self,
+        candidate: &T,
+        max_depth: usize,
+    ) -> Result<bool, DepthExceeded> {
+        self.evaluate_iteratively(Some(max_depth), &mut |f| f.is_satisfied_by(candidate))
+    }
+
+    // The result of the composition when its leaves evaluate as `leaf` says.
+    pub(crate) fn evaluate_iteratively(
+        &self,
+        max_depth: Option<usize>,
+        leaf: &mut impl FnMut(&Arc<dyn Specification<T>>) -> bool,
+    ) -> Result<bool, DepthExceeded> {
+        self.evaluate_on_stack(&mut Vec::new(), max_depth, leaf)
+    }
+
+    // Like `evaluate_iteratively`, reusing the allocation of `stack`, which is left empty.
+    fn evaluate_on_stack<'a>(
+        &'a self,
+        stack: &mut Vec<Frame<'a, T>>,
+        max_depth: Option<usize>,
+        leaf: &mut impl FnMut(&Arc<dyn Specification<T>>) -> bool,
+    ) -> Result<bool, DepthExceeded> {
+        let exceeds = |depth: usize| max_depth.is_some_and(|max_depth| depth > max_depth);
+        if exceeds(1) {
+            return Err(DepthExceeded {
+                max_depth: max_depth.unwrap_or_default(),
+            });
+        }
+        stack.clear();
+        stack.push(Frame::new(self));
+        let mut result = None;
+        while let Some(frame) = stack.last_mut() {
+            if let Some(satisfied) = result.take() {
+                frame.record(satisfied);
+            }
+            if let Some(satisfied) = frame.decided(leaf) {
+                stack.pop();
+                result = Some(satisfied);
+                continue;
+            }
+            let child = frame.node.child(frame.next);
+            frame.next += 1;
+            if exceeds(stack.len() + 1) {
+                stack.clear();
+                return Err(DepthExceeded {
+                    max_depth: max_depth.unwrap_or_default(),
+                });
+            }
+            stack.push(Frame::new(child));
+        }
+        Ok(result.unwrap_or_default())
+    }
+
+    /// An evaluator for repeated batches of candidates, see [`BatchEvaluator::evaluate_batch`].
+    pub fn batch_evaluator(&self) -> BatchEvaluator<'_, T> {
+        BatchEvaluator {
+            specification: self,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Evaluates the composition with the given `policy`, the result is the same either way.
+    pub fn is_satisfied_by_with(&self, candidate: &T, policy: EvalPolicy) -> bool {
+        match policy {
+            EvalPolicy::ShortCircuit => self.is_satisfied_by(candidate),
+            EvalPolicy::Exhaustive => self.evaluate_exhaustive(candidate),
+        }
+    }
+
+    /// Compiles the composition into a single closure, resolving the combinator structure
+    /// once instead of matching on it at every evaluation. The closure borrows the leaves.
+    pub fn compile(&self) -> Predicate<'_, T> {
+        match self {
+            Self::Specification(f) => Box::new(move |candidate| f.is_satisfied_by(candidate)),
+            Self::And(specifications) => {
+                let compiled = Self::compile_all(specifications);
+                Box::new(move |candidate| compiled.iter().all(|f| f(candidate)))
+            }
+            Self::Or(specifications) => {
+                let compiled = Self::compile_all(specifications);
+                Box::new(move |candidate| compiled.iter().any(|f| f(candidate)))
+            }
+            Self::Xor(specifications) => {
+                let compiled = Self::compile_all(specifications);
+                Box::new(move |candidate| compiled.iter().filter(|f| f(candidate)).count() % 2 == 1)
+            }
+            Self::Invert(specification) => {
+                let compiled = specification.compile();
+                Box::new(move |candidate| !compiled(candidate))
+            }
+            Self::True => Box::new(|_| true),
+            Self::False => Box::new(|_| false),
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let compiled: Vec<(f64, Predicate<'_, T>)> = specifications
+                    .iter()
+                    .map(|(weight, specification)| (*weight, specification.compile()))
+                    .collect();
+                Box::new(move |candidate| {
+                    let weight: f64 = compiled
+                        .iter()
+                        .filter(|(_, f)| f(candidate))
+                        .map(|(weight, _)| weight)
+                        .sum();
+                    weight >= *threshold
+                })
+            }
+            Self::MinSatisfied(count, specifications) => {
+                let compiled = Self::compile_all(specifications);
+                Box::new(move |candidate| {
+                    compiled.iter().filter(|f| f(candidate)).count() >= *count
+                })
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                let compiled = Self::compile_all(specifications);
+                Box::new(move |candidate| {
+                    compiled.iter().filter(|f| f(candidate)).count() <= *count
+                })
+            }
+        }
+    }
+
+    /// The result together with how close the candidate came, as the share of the leaves it
+    /// satisfies across the whole tree, from 0 to 1. Every leaf is evaluated, wherever it is
+    /// and whatever decides the result. A tree without leaves scores 1 when satisfied, else 0.
+    pub fn evaluate_scored(&self, candidate: &T) -> (bool, f64) {
+        let satisfied = self.is_satisfied_by(candidate);
+        let mut leaves = 0;
+        let mut satisfied_leaves = 0;
+        self.for_each_leaf(&mut |leaf| {
+            leaves += 1;
+            satisfied_leaves += usize::from(leaf.is_satisfied_by(candidate));
+        });
+        let score = if leaves == 0 {
+            f64::from(u8::from(satisfied))
+        } else {
+            satisfied_leaves as f64 / leaves as f64
+        };
+        (satisfied, score)
+    }
+
+    /// Precomputes the result for every value of a small domain, typically all the variants
+    /// of a fieldless enum, so later checks are a single index read.
+    ///
+    /// The table follows the order of `all_values`: list the variants in declaration order
+    /// and look them up with `table[value as usize]`.
+    pub fn compile_enum_table(&self, all_values: &[T]) -> Vec<bool> {
+        all_values
+            .iter()
+            .map(|value| self.is_satisfied_by(value))
+            .collect()
+    }
+
+    /// Evaluates like [`crate::Specification::is_satisfied_by`] while counting the visited
+    /// nodes, to see how effective short-circuiting is without a full trace.
+    pub fn evaluate_with_stats(&self, candidate: &T) -> (bool, EvalStats) {
+        let mut stats = EvalStats::default();
+        let result = self.evaluate_counting(candidate, &mut stats);
+        (result, stats)
+    }
+
+    fn evaluate_counting(&self, candidate: &T, stats: &mut EvalStats) -> bool {
+        if !matches!(self, Self::Specification(..) | Self::True | Self::False) {
+            stats.combinators += 1;
+        }
+        match self {
+            Self::Specification(f) => {
+                stats.leaves += 1;
+                f.is_satisfied_by(candidate)
+            }
+            Self::And(specifications) => specifications
+                .iter()
+                .all(|specification| specification.evaluate_counting(candidate, stats)),
+            Self::Or(specifications) => specifications
+                .iter()
+                .any(|specification| specification.evaluate_counting(candidate, stats)),
+            Self::Xor(specifications) => {
+                specifications
+                    .iter()
+                    .filter(|specification| specification.evaluate_counting(candidate, stats))
+                    .count()
+                    % 2
+                    == 1
+            }
+            Self::Invert(specification) => !specification.evaluate_counting(candidate, stats),
+            Self::True => true,
+            Self::False => false,
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let weight: f64 = specifications
+                    .iter()
+                    .filter(|(_, specification)| specification.evaluate_counting(candidate, stats))
+                    .map(|(weight, _)| weight)
+                    .sum();
+                weight >= *threshold
+            }
+            Self::MinSatisfied(count, specifications) => {
+                Self::count_counting(specifications, candidate, stats) >= *count
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                Self::count_counting(specifications, candidate, stats) <= *count
+            }
+        }
+    }
+
+    fn evaluate_exhaustive(&self, candidate: &T) -> bool {
+        match self {
+            Self::Specification(f) => f.is_satisfied_by(candidate),
+            Self::And(specifications) => {
+                Self::count_exhaustive(specifications, candidate) == specifications.len()
+            }
+            Self::Or(specifications) => Self::count_exhaustive(specifications, candidate) > 0,
+            Self::Xor(specifications) => Self::count_exhaustive(specifications, candidate) % 2 == 1,
+            Self::Invert(specification) => !specification.evaluate_exhaustive(candidate),
+            Self::True => true,
+            Self::False => false,
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let weight: f64 = specifications
+                    .iter()
+                    .filter(|(_, specification)| specification.evaluate_exhaustive(candidate))
+                    .map(|(weight, _)| weight)
+                    .sum();
+                weight >= *threshold
+            }
+            Self::MinSatisfied(count, specifications) => {
+                Self::count_exhaustive(specifications, candidate) >= *count
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                Self::count_exhaustive(specifications, candidate) <= *count
+            }
+        }
+    }
+
+    fn child_count(&self) -> usize {
+        match self {
+            Self::And(specifications)
+            | Self::Or(specifications)
+            | Self::Xor(specifications)
+            | Self::MinSatisfied(_, specifications)
+            | Self::MaxSatisfied(_, specifications) => specifications.len(),
+            Self::WeightedAtLeast(_, specifications) => specifications.len(),
+            Self::Invert(..) => 1,
+            Self::Specification(..) | Self::True | Self::False => 0,
+        }
+    }
+
+    // Like `children()[index]`, without collecting the children.
+    fn child(&self, index: usize) -> &Self {
+        match self {
+            Self::And(specifications)
+            | Self::Or(specifications)
+            | Self::Xor(specifications)
+            | Self::MinSatisfied(_, specifications)
+            | Self::MaxSatisfied(_, specifications) => &specifications[index],
+            Self::WeightedAtLeast(_, specifications) => &specifications[index].1,
+            Self::Invert(specification) => specification,
+            Self::Specification(..) | Self::True | Self::False => {
+                unreachable!("leaves have no children")
+            }
+        }
+    }
+
+    fn count_exhaustive(specifications: &[Self], candidate: &T) -> usize {
+        specifications
+            .iter()
+            .filter(|specification| specification.evaluate_exhaustive(candidate))
+            .count()
+    }
+
+    fn count_counting(specifications: &[Self], candidate: &T, stats: &mut EvalStats) -> usize {
+        specifications
+            .iter()
+            .filter(|specification| specification.evaluate_counting(candidate, stats))
+            .count()
+    }
+
+    fn compile_all(specifications: &[Self]) -> Vec<Predicate<'_, T>> {
+        specifications.iter().map(Self::compile).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Weekday {
+        Monday,
+        Tuesday,
+        Wednesday,
+        Thursday,
+        Friday,
+        Saturday,
+        Sunday,
+    }
+
+    const WEEKDAYS: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    #[derive(Debug)]
+    struct Weekend;
+
+    impl Specification<Weekday> for Weekend {
+        fn is_satisfied_by(&self, candidate: &Weekday) -> bool {
+            matches!(candidate, Weekday::Saturday | Weekday::Sunday)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Is(Weekday);
+
+    impl Specification<Weekday> for Is {
+        fn is_satisfied_by(&self, candidate: &Weekday) -> bool {
+            *candidate as usize == self.0 as usize
+        }
+    }
+
+    #[test]
+    fn test_compile() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .or(Zero {})
+            .or(GreaterThan { value: 20 }
+                .xor(LessThan { value: 30 })
+                .invert());
+
+        let compiled = specification.compile();
+
+        for candidate in -10..40 {
+            assert_eq!(
+                compiled(&candidate),
+                specification.is_satisfied_by(&candidate),
+                "candidate {}",
+                candidate
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_with_stats_short_circuit() {
+        let specification = GreaterThan { value: 5 }
+            .or(LessThan { value: 10 })
+            .or(Zero {}.invert());
+
+        let (result, stats) = specification.evaluate_with_stats(&6);
+
+        assert!(result);
+        assert_eq!(
+            stats,
+            EvalStats {
+                leaves: 1,
+                combinators: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_evaluate_with_stats_full() {
+        let specification = GreaterThan { value: 5 }
+            .or(LessThan { value: 0 })
+            .or(Zero {}.invert());
+
+        let (result, stats) = specification.evaluate_with_stats(&0);
+
+        assert!(!result);
+        assert_eq!(
+            stats,
+            EvalStats {
+                leaves: 3,
+                combinators: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_is_satisfied_by_with() {
+        let (greater_than, greater_than_evaluations) = counting(GreaterThan { value: 5 });
+        let (less_than, less_than_evaluations) = counting(LessThan { value: 10 });
+        let (zero, zero_evaluations) = counting(Zero {});
+        let specification = greater_than.and(less_than).or(zero);
+        let evaluations = || {
+            count(&greater_than_evaluations)
+                + count(&less_than_evaluations)
+                + count(&zero_evaluations)
+        };
+
+        assert!(specification.is_satisfied_by_with(&7, EvalPolicy::ShortCircuit));
+        assert_eq!(evaluations(), 2);
+        assert!(!specification.is_satisfied_by_with(&3, EvalPolicy::ShortCircuit));
+        assert_eq!(evaluations(), 4);
+
+        assert!(specification.is_satisfied_by_with(&7, EvalPolicy::Exhaustive));
+        assert_eq!(evaluations(), 7);
+        assert!(!specification.is_satisfied_by_with(&3, EvalPolicy::Exhaustive));
+        assert_eq!(evaluations(), 10);
+    }
+
+    #[test]
+    fn test_is_satisfied_by_limited() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .or(Zero {}.invert())
+            .or(SpecificationCompositions::WeightedAtLeast(
+                1.5,
+                vec![
+                    (1.0, GreaterThan { value: 20 }.composite()),
+                    (1.0, LessThan { value: 30 }.composite()),
+                ],
+            ));
+
+        for candidate in -10..40 {
+            assert_eq!(
+                specification.is_satisfied_by_limited(&candidate, 3),
+                Ok(specification.is_satisfied_by(&candidate)),
+                "candidate {}",
+                candidate
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_satisfied_by_limited_too_deep() {
+        let mut specification = Zero {}.composite();
+        for _ in 0..1_000 {
+            specification = SpecificationCompositions::And(vec![
+                GreaterThan { value: -1 }.composite(),
+                specification,
+            ]);
+        }
+
+        assert_eq!(
+            specification.is_satisfied_by_limited(&0, 100),
+            Err(DepthExceeded { max_depth: 100 })
+        );
+        assert_eq!(specification.is_satisfied_by_limited(&0, 1_001), Ok(true));
+        assert_eq!(
+            specification.is_satisfied_by_limited(&0, 1_000),
+            Err(DepthExceeded { max_depth: 1_000 })
+        );
+        // The first child fails, the deep one is never reached.
+        assert_eq!(specification.is_satisfied_by_limited(&-5, 2), Ok(false));
+        assert_eq!(
+            Zero {}.composite().is_satisfied_by_limited(&0, 0),
+            Err(DepthExceeded { max_depth: 0 })
+        );
+    }
+
+    #[test]
+    fn test_is_satisfied_by_deep() {
+        let mut specification = Zero {}.composite();
+        for _ in 0..100_000 {
+            specification = SpecificationCompositions::And(vec![
+                GreaterThan { value: -1 }.composite(),
+                specification,
+            ]);
+        }
+
+        assert!(specification.is_satisfied_by(&0));
+        assert!(!specification.is_satisfied_by(&1));
+        assert!(!specification.is_satisfied_by(&-1));
+
+        // Dropping recurses as deep as the tree, take it apart one level at a time instead.
+        while let SpecificationCompositions::And(mut specifications) = specification {
+            specification = specifications.pop().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_evaluate_batch() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 }.or(Zero {}));
+        let mut evaluator = specification.batch_evaluator();
+        let mut out = vec![true; 10];
+
+        evaluator.evaluate_batch(&[0, 6, 12], &mut out);
+        assert_eq!(out, [false, true, false]);
+
+        evaluator.evaluate_batch(&[9, 10], &mut out);
+        assert_eq!(out, [true, false]);
+
+        evaluator.evaluate_batch(&[], &mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_scored() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(GreaterThan { value: 8 })
+            .and(LessThan { value: 100 }.or(Zero {}));
+
+        assert_eq!(specification.evaluate_scored(&7), (false, 0.6));
+        assert_eq!(specification.evaluate_scored(&9), (true, 0.8));
+        assert_eq!(specification.evaluate_scored(&200), (false, 0.4));
+    }
+
+    #[test]
+    fn test_evaluate_scored_without_leaves() {
+        assert_eq!(
+            SpecificationCompositions::<i32>::True.evaluate_scored(&0),
+            (true, 1.0)
+        );
+        assert_eq!(
+            SpecificationCompositions::<i32>::False.evaluate_scored(&0),
+            (false, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_compile_enum_table() {
+        let specification = Weekend.invert().and(Is(Weekday::Monday).invert());
+
+        let table = specification.compile_enum_table(&WEEKDAYS);
+
+        assert_eq!(table, vec![false, true, true, true, true, false, false]);
+        for day in WEEKDAYS {
+            assert_eq!(table[day as usize], specification.is_satisfied_by(&day));
+        }
+    }
+}
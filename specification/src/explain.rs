@@ -0,0 +1,475 @@
+use std::sync::Arc;
+
+#[cfg(feature = "json")]
+use crate::json::JsonValue;
+use crate::{Specification, SpecificationCompositions};
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// The most important failing leaf of the unsatisfied remainder, by [`Specification::importance`].
+    /// When several leaves are equally important the first one wins.
+    pub fn primary_failure(&self, candidate: &T) -> Option<Arc<dyn Specification<T>>> {
+        let reminder = self.failure_reminder(candidate)?;
+        let mut primary: Option<Arc<dyn Specification<T>>> = None;
+        reminder.for_each_leaf(&mut |leaf| {
+            if primary
+                .as_ref()
+                .is_none_or(|primary| leaf.importance() > primary.importance())
+            {
+                primary = Some(leaf.clone());
+            }
+        });
+        primary
+    }
+
+    /// The names of at most `max_items` leaves of the unsatisfied remainder, the most important
+    /// first by [`Specification::importance`], to keep a failure explanation short enough
+    /// for a UI. Each name is listed once, equally important leaves in the order they appear.
+    pub fn concise_reason(&self, candidate: &T, max_items: usize) -> Vec<String> {
+        let Some(reminder) = self.failure_reminder(candidate) else {
+            return Vec::new();
+        };
+        let mut reasons: Vec<(String, u32)> = Vec::new();
+        reminder.for_each_leaf(&mut |leaf| {
+            let name = leaf.name();
+            match reasons.iter_mut().find(|(reason, _)| *reason == name) {
+                Some((_, importance)) => *importance = (*importance).max(leaf.importance()),
+                None => reasons.push((name, leaf.importance())),
+            }
+        });
+        reasons.sort_by(|(_, a), (_, b)| b.cmp(a));
+        reasons
+            .into_iter()
+            .take(max_items)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// A one-line outcome for logs, `PASS` or `FAIL: ` followed by the names of the leaves in
+    /// the unsatisfied remainder, each listed once, in the order they appear. A failure
+    /// without any leaf to blame, like a `False`, is just `FAIL`.
+    pub fn satisfied_summary(&self, candidate: &T) -> String {
+        let Some(reminder) = self.failure_reminder(candidate) else {
+            return "PASS".to_string();
+        };
+        let mut names: Vec<String> = Vec::new();
+        reminder.for_each_leaf(&mut |leaf| {
+            let name = leaf.name();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        });
+        if names.is_empty() {
+            return "FAIL".to_string();
+        }
+        format!("FAIL: {}", names.join(", "))
+    }
+
+    /// Which alternative of an `Or` the candidate qualified under, as the index path of the
+    /// first satisfied child, descending into it while it is an `Or` itself.
+    /// `None` when nothing is satisfied, or the node isn't an `Or`.
+    pub fn first_matching_branch(&self, candidate: &T) -> Option<Vec<usize>> {
+        let Self::Or(specifications) = self else {
+            return None;
+        };
+        let (i, specification) = specifications
+            .iter()
+            .enumerate()
+            .find(|(_, specification)| specification.is_satisfied_by(candidate))?;
+        let mut path = vec![i];
+        path.extend(
+            specification
+                .first_matching_branch(candidate)
+                .unwrap_or_default(),
+        );
+        Some(path)
+    }
+
+    /// How many of the direct children of a combinator hold, as `(satisfied, total)`,
+    /// e.g. to report "3 of 5". Leaves and constants have no children and return `None`.
+    pub fn matched_children(&self, candidate: &T) -> Option<(usize, usize)> {
+        if matches!(self, Self::Specification(..) | Self::True | Self::False) {
+            return None;
+        }
+        let children = self.children();
+        let satisfied = children
+            .iter()
+            .filter(|child| child.is_satisfied_by(candidate))
+            .count();
+        Some((satisfied, children.len()))
+    }
+
+    /// Renders the pass/fail of every node as a nested Markdown bullet list.
+    /// Every node is evaluated on its own, regardless of short-circuiting.
+    pub fn explain_markdown(&self, candidate: &T) -> String {
+        let mut output = String::new();
+        self.visit_explained(candidate, 0, &mut |depth, node, satisfied| {
+            let marker = if satisfied { "✅" } else { "❌" };
+            output.push_str(&format!(
+                "{}- {} {}\n",
+                "  ".repeat(depth),
+                marker,
+                node.name()
+            ));
+        });
+        output
+    }
+
+    /// The per-node results as JSON, for front-ends to render, such as
+    /// `{"type":"and","result":false,"children":[{"type":"leaf","name":"Zero","result":false}]}`.
+    /// Every node is evaluated on its own, regardless of short-circuiting.
+    #[cfg(feature = "json")]
+    pub fn explain_json(&self, candidate: &T) -> JsonValue {
+        let node_type = match self {
+            Self::Specification(..) => "leaf".to_string(),
+            _ => self.name(),
+        };
+        let mut fields = vec![("type".to_string(), JsonValue::String(node_type))];
+        if let Self::Specification(f) = self {
+            fields.push(("name".to_string(), JsonValue::String(f.name())));
+        }
+        fields.push((
+            "result".to_string(),
+            JsonValue::Bool(self.is_satisfied_by(candidate)),
+        ));
+        if !matches!(self, Self::Specification(..) | Self::True | Self::False) {
+            let children = self
+                .children()
+                .into_iter()
+                .map(|child| child.explain_json(candidate))
+                .collect();
+            fields.push(("children".to_string(), JsonValue::Array(children)));
+        }
+        JsonValue::Object(fields)
+    }
+
+    /// Renders the tree explanation for a terminal, satisfied nodes in green and failing ones
+    /// in red. Falls back to plain text when the `NO_COLOR` environment variable is set.
+    #[cfg(feature = "color")]
+    pub fn explain_colored(&self, candidate: &T) -> String {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.explain_terminal(candidate, !no_color)
+    }
+
+    #[cfg(feature = "color")]
+    fn explain_terminal(&self, candidate: &T, colored: bool) -> String {
+        const GREEN: &str = "\x1b[32m";
+        const RED: &str = "\x1b[31m";
+        const RESET: &str = "\x1b[0m";
+
+        let mut output = String::new();
+        self.visit_explained(candidate, 0, &mut |depth, node, satisfied| {
+            let (color, marker) = if satisfied {
+                (GREEN, "✔")
+            } else {
+                (RED, "✘")
+            };
+            output.push_str(&"  ".repeat(depth));
+            if colored {
+                output.push_str(&format!("{}{} {}{}\n", color, marker, node.name(), RESET));
+            } else {
+                output.push_str(&format!("{} {}\n", marker, node.name()));
+            }
+        });
+        output
+    }
+
+    /// Renders every node on its own line, numbered like a document outline
+    /// (`1.`, `1.1.`, `1.2.1.`), so nodes can be referred to in logs.
+    /// Combinators show their type, leaves are shown as `leaf` followed by their name.
+    pub fn to_outline(&self) -> String {
+        let mut output = String::new();
+        self.write_outline("1.", &mut output);
+        output
+    }
+
+    fn write_outline(&self, number: &str, output: &mut String) {
+        match self {
+            Self::Specification(f) => output.push_str(&format!("{} leaf {}\n", number, f.name())),
+            _ => output.push_str(&format!("{} {}\n", number, self.name())),
+        }
+        for (i, child) in self.children().into_iter().enumerate() {
+            child.write_outline(&format!("{}{}.", number, i + 1), output);
+        }
+    }
+
+    // Visits every node in pre-order with its depth and its own result.
+    fn visit_explained(
+        &self,
+        candidate: &T,
+        depth: usize,
+        visit: &mut impl FnMut(usize, &Self, bool),
+    ) {
+        visit(depth, self, self.is_satisfied_by(candidate));
+        for child in self.children() {
+            child.visit_explained(candidate, depth + 1, visit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[derive(Debug)]
+    struct Weighted {
+        value: i32,
+        importance: u32,
+    }
+
+    impl Specification<i32> for Weighted {
+        fn is_satisfied_by(&self, candidate: &i32) -> bool {
+            candidate > &self.value
+        }
+        fn importance(&self) -> u32 {
+            self.importance
+        }
+    }
+
+    #[test]
+    fn test_concise_reason() {
+        let weighted = |name, value, importance| Named {
+            name,
+            specification: Weighted { value, importance },
+        };
+        let specification = weighted("A", 1, 1)
+            .and(weighted("B", 2, 5))
+            .and(weighted("C", 3, 3))
+            .and(weighted("D", -1, 9));
+
+        assert_eq!(specification.concise_reason(&0, 2), vec!["B", "C"]);
+        assert_eq!(specification.concise_reason(&0, 5), vec!["B", "C", "A"]);
+        assert_eq!(specification.concise_reason(&0, 0), Vec::<String>::new());
+        assert!(specification.concise_reason(&4, 2).is_empty());
+    }
+
+    #[test]
+    fn test_concise_reason_inverted() {
+        let specification = LessThan { value: 10 }.and(Zero {}.invert());
+
+        assert_eq!(specification.concise_reason(&0, 2), vec!["Zero"]);
+        assert_eq!(specification.concise_reason(&12, 2), vec!["LessThan"]);
+        assert!(specification.concise_reason(&5, 2).is_empty());
+    }
+
+    #[test]
+    fn test_satisfied_summary_inverted() {
+        let specification = LessThan { value: 10 }.and(Zero {}.invert());
+
+        assert_eq!(specification.satisfied_summary(&5), "PASS");
+        assert_eq!(specification.satisfied_summary(&0), "FAIL: Zero");
+        assert_eq!(specification.satisfied_summary(&12), "FAIL: LessThan");
+    }
+
+    #[test]
+    fn test_satisfied_summary_false() {
+        let specification = SpecificationCompositions::And(vec![
+            LessThan { value: 10 }.composite(),
+            SpecificationCompositions::False,
+        ]);
+
+        assert_eq!(specification.satisfied_summary(&5), "FAIL");
+        assert_eq!(specification.satisfied_summary(&12), "FAIL: LessThan");
+    }
+
+    #[test]
+    fn test_primary_failure() {
+        let specification = Weighted {
+            value: 1,
+            importance: 1,
+        }
+        .and(Weighted {
+            value: 2,
+            importance: 5,
+        })
+        .and(Weighted {
+            value: 3,
+            importance: 3,
+        })
+        .and(Weighted {
+            value: 100,
+            importance: 10,
+        });
+
+        let primary = specification.primary_failure(&0).unwrap();
+        assert_eq!(primary.importance(), 10);
+
+        let primary = specification.primary_failure(&101);
+        assert!(primary.is_none());
+    }
+
+    #[test]
+    fn test_explain_markdown() {
+        let specification =
+            GreaterThan { value: 5 }.and(LessThan { value: 10 }.or(Zero {}.invert()));
+
+        let markdown = specification.explain_markdown(&0);
+
+        assert_eq!(
+            markdown,
+            "- ❌ and\n  - ❌ GreaterThan\n  - ✅ or\n    - ✅ LessThan\n    - ❌ not\n      - ✅ Zero\n"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_explain_json() {
+        let specification = SpecificationCompositions::And(vec![
+            GreaterThan { value: 5 }.composite(),
+            LessThan { value: 10 }.or(Zero {}.invert()),
+            SpecificationCompositions::True,
+        ]);
+
+        let json = specification.explain_json(&0);
+
+        fn child(node: &JsonValue, i: usize) -> &JsonValue {
+            node.get("children")
+                .and_then(|children| children.get_index(i))
+                .unwrap()
+        }
+        let result = |node: &JsonValue| node.get("result").cloned();
+        assert_eq!(result(&json), Some(JsonValue::Bool(false)));
+        assert_eq!(result(child(&json, 0)), Some(JsonValue::Bool(false)));
+        assert_eq!(result(child(&json, 1)), Some(JsonValue::Bool(true)));
+        assert_eq!(
+            result(child(child(&json, 1), 1)),
+            Some(JsonValue::Bool(false))
+        );
+        assert_eq!(
+            json.to_string(),
+            concat!(
+                r#"{"type":"and","result":false,"children":["#,
+                r#"{"type":"leaf","name":"GreaterThan","result":false},"#,
+                r#"{"type":"or","result":true,"children":["#,
+                r#"{"type":"leaf","name":"LessThan","result":true},"#,
+                r#"{"type":"not","result":false,"children":["#,
+                r#"{"type":"leaf","name":"Zero","result":true}]}]},"#,
+                r#"{"type":"true","result":true}]}"#
+            )
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn test_explain_terminal_colored() {
+        let specification = GreaterThan { value: 5 }.and(Zero {}.invert());
+
+        let explanation = specification.explain_terminal(&0, true);
+
+        assert_eq!(
+            explanation,
+            "\x1b[31m✘ and\x1b[0m\n  \x1b[31m✘ GreaterThan\x1b[0m\n  \x1b[31m✘ not\x1b[0m\n    \x1b[32m✔ Zero\x1b[0m\n"
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn test_explain_terminal_plain() {
+        let specification = GreaterThan { value: 5 }.and(Zero {}.invert());
+
+        let explanation = specification.explain_terminal(&6, false);
+
+        assert!(!explanation.contains('\x1b'));
+        assert_eq!(explanation, "✔ and\n  ✔ GreaterThan\n  ✔ not\n    ✘ Zero\n");
+    }
+
+    // The only test touching `NO_COLOR`, so it doesn't race with the others.
+    #[cfg(feature = "color")]
+    #[test]
+    fn test_explain_colored_no_color() {
+        let specification = GreaterThan { value: 5 }.and(Zero {}.invert());
+
+        std::env::set_var("NO_COLOR", "1");
+        let plain = specification.explain_colored(&6);
+        std::env::set_var("NO_COLOR", "");
+        let colored = specification.explain_colored(&6);
+        std::env::remove_var("NO_COLOR");
+
+        assert_eq!(plain, specification.explain_terminal(&6, false));
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(colored, specification.explain_terminal(&6, true));
+        assert!(colored.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_to_outline() {
+        let specification = SpecificationCompositions::And(vec![
+            GreaterThan { value: 5 }.composite(),
+            LessThan { value: 10 }.or(Zero {}.invert()),
+            SpecificationCompositions::True,
+        ]);
+
+        assert_eq!(
+            specification.to_outline(),
+            "1. and\n\
+             1.1. leaf GreaterThan\n\
+             1.2. or\n\
+             1.2.1. leaf LessThan\n\
+             1.2.2. not\n\
+             1.2.2.1. leaf Zero\n\
+             1.3. true\n"
+        );
+    }
+
+    #[test]
+    fn test_first_matching_branch() {
+        let specification = SpecificationCompositions::Or(vec![
+            GreaterThan { value: 10 }.composite(),
+            Zero {}.or(LessThan { value: 0 }),
+            LessThan { value: 5 }.and(GreaterThan { value: 1 }),
+        ]);
+
+        assert_eq!(specification.first_matching_branch(&20), Some(vec![0]));
+        assert_eq!(specification.first_matching_branch(&-3), Some(vec![1, 1]));
+        assert_eq!(specification.first_matching_branch(&3), Some(vec![2]));
+        assert_eq!(specification.first_matching_branch(&7), None);
+        assert_eq!(Zero {}.composite().first_matching_branch(&0), None);
+    }
+
+    #[test]
+    fn test_matched_children() {
+        let specification = GreaterThan { value: 1 }
+            .and(GreaterThan { value: 2 })
+            .and(LessThan { value: 10 })
+            .and(Zero {})
+            .and(LessThan { value: 0 });
+
+        assert_eq!(specification.matched_children(&5), Some((3, 5)));
+        assert_eq!(Zero {}.composite().matched_children(&5), None);
+    }
+
+    #[test]
+    fn test_primary_failure_ignores_satisfied_leaves() {
+        let specification = Weighted {
+            value: 1,
+            importance: 1,
+        }
+        .and(Weighted {
+            value: 2,
+            importance: 5,
+        })
+        .and(Weighted {
+            value: 3,
+            importance: 3,
+        })
+        .and(Weighted {
+            value: -100,
+            importance: 10,
+        });
+
+        let primary = specification.primary_failure(&0).unwrap();
+        assert_eq!(primary.importance(), 5);
+    }
+
+    #[test]
+    fn test_primary_failure_inverted() {
+        let specification = LessThan { value: 10 }.and(Zero {}.invert());
+
+        assert_eq!(specification.primary_failure(&0).unwrap().name(), "Zero");
+        assert_eq!(
+            specification.primary_failure(&12).unwrap().name(),
+            "LessThan"
+        );
+        assert!(specification.primary_failure(&5).is_none());
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::Specification;
+
+#[derive(Debug, Clone)]
+pub struct GreaterThan {
+    pub value: i32,
+}
+impl Specification<i32> for GreaterThan {
+    fn is_satisfied_by(&self, candidate: &i32) -> bool {
+        candidate > &self.value
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LessThan {
+    pub value: i32,
+}
+impl Specification<i32> for LessThan {
+    fn is_satisfied_by(&self, candidate: &i32) -> bool {
+        candidate < &self.value
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Zero {}
+
+impl Specification<i32> for Zero {
+    fn is_satisfied_by(&self, candidate: &i32) -> bool {
+        candidate == &0
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+// Gives a leaf its own name, so leaves of the same type can be told apart in a `Registry`.
+#[derive(Debug)]
+pub struct Named<S> {
+    pub name: &'static str,
+    pub specification: S,
+}
+
+impl<S: Specification<i32>> Specification<i32> for Named<S> {
+    fn is_satisfied_by(&self, candidate: &i32) -> bool {
+        self.specification.is_satisfied_by(candidate)
+    }
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+    fn importance(&self) -> u32 {
+        self.specification.importance()
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.specification.eq_by_debug()
+    }
+}
+
+pub fn greater_than_5() -> Named<GreaterThan> {
+    Named {
+        name: "GreaterThan5",
+        specification: GreaterThan { value: 5 },
+    }
+}
+
+pub fn less_than_10() -> Named<LessThan> {
+    Named {
+        name: "LessThan10",
+        specification: LessThan { value: 10 },
+    }
+}
+
+#[derive(Debug)]
+pub struct Counting<S> {
+    specification: S,
+    evaluations: Arc<AtomicUsize>,
+}
+
+impl<S: Specification<i32>> Specification<i32> for Counting<S> {
+    fn is_satisfied_by(&self, candidate: &i32) -> bool {
+        self.evaluations.fetch_add(1, Ordering::SeqCst);
+        self.specification.is_satisfied_by(candidate)
+    }
+    fn name(&self) -> String {
+        self.specification.name()
+    }
+}
+
+// Wraps the specification so it counts its evaluations in the returned counter.
+pub fn counting<S>(specification: S) -> (Counting<S>, Arc<AtomicUsize>) {
+    let evaluations = Arc::new(AtomicUsize::new(0));
+    let counting = Counting {
+        specification,
+        evaluations: evaluations.clone(),
+    };
+    (counting, evaluations)
+}
+
+pub fn count(evaluations: &AtomicUsize) -> usize {
+    evaluations.load(Ordering::SeqCst)
+}
@@ -0,0 +1,182 @@
+// String format validation, with the validators pluggable behind a trait.
+// The built-in ones are deliberately simple structural checks, not full RFC parsers.
+
+use std::fmt::Debug;
+
+use crate::Specification;
+
+/// Tells whether a string is in a given format.
+pub trait FormatValidator: Debug {
+    fn is_valid(&self, s: &str) -> bool;
+}
+
+/// Satisfied by strings `validator` accepts.
+pub fn format(validator: impl FormatValidator + 'static) -> Format {
+    Format {
+        validator: Box::new(validator),
+    }
+}
+
+#[derive(Debug)]
+pub struct Format {
+    validator: Box<dyn FormatValidator>,
+}
+
+impl Specification<String> for Format {
+    fn is_satisfied_by(&self, candidate: &String) -> bool {
+        self.validator.is_valid(candidate)
+    }
+    fn name(&self) -> String {
+        format!("{:?}", self.validator)
+    }
+}
+
+/// `local@domain`, the domain having at least two labels and a top level one of letters only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Email;
+
+impl FormatValidator for Email {
+    fn is_valid(&self, s: &str) -> bool {
+        let Some((local, domain)) = s.split_once('@') else {
+            return false;
+        };
+        let local_valid = !local.is_empty()
+            && !local.starts_with('.')
+            && !local.ends_with('.')
+            && !local.contains("..")
+            && local
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c));
+        let top_level = domain.rsplit('.').next().unwrap_or_default();
+        local_valid
+            && domain.contains('.')
+            && is_host(domain)
+            && top_level.len() >= 2
+            && top_level.chars().all(|c| c.is_ascii_alphabetic())
+    }
+}
+
+/// `scheme://host[:port][/path][?query][#fragment]`, the host being a domain name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Url;
+
+impl FormatValidator for Url {
+    fn is_valid(&self, s: &str) -> bool {
+        let Some((scheme, rest)) = s.split_once("://") else {
+            return false;
+        };
+        let scheme_valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let (host, port) = match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        };
+        scheme_valid
+            && is_host(host)
+            && port.is_none_or(|port| {
+                !port.is_empty() && port.len() <= 5 && port.chars().all(|c| c.is_ascii_digit())
+            })
+            && !s.chars().any(char::is_whitespace)
+    }
+}
+
+// Dot separated labels of letters, digits and inner hyphens.
+fn is_host(host: &str) -> bool {
+    host.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_email() {
+        let specification = format(Email);
+
+        for valid in ["john@example.com", "john.doe+jobs@mail.example.co.uk"] {
+            assert!(
+                specification.is_satisfied_by(&valid.to_string()),
+                "{}",
+                valid
+            );
+        }
+        for invalid in [
+            "",
+            "john",
+            "john@",
+            "@example.com",
+            "john@example",
+            "john@@example.com",
+            "john@exam ple.com",
+            "john..doe@example.com",
+            "john@-example.com",
+            "john@example.c0m",
+        ] {
+            assert!(
+                !specification.is_satisfied_by(&invalid.to_string()),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_url() {
+        let specification = format(Url);
+
+        for valid in [
+            "https://example.com",
+            "http://localhost:8080/jobs?id=1#apply",
+            "ftp://files.example.com/cv.pdf",
+        ] {
+            assert!(
+                specification.is_satisfied_by(&valid.to_string()),
+                "{}",
+                valid
+            );
+        }
+        for invalid in [
+            "",
+            "example.com",
+            "https://",
+            "https:/example.com",
+            "1http://example.com",
+            "https://example..com",
+            "https://example.com:port",
+            "https://exa mple.com/a b",
+        ] {
+            assert!(
+                !specification.is_satisfied_by(&invalid.to_string()),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[derive(Debug)]
+    struct NoDigits;
+
+    impl FormatValidator for NoDigits {
+        fn is_valid(&self, s: &str) -> bool {
+            !s.chars().any(|c| c.is_ascii_digit())
+        }
+    }
+
+    #[test]
+    fn test_custom_validator() {
+        let specification = format(NoDigits).and(format(Email));
+
+        assert!(specification.is_satisfied_by(&"john@example.com".to_string()));
+        assert!(!specification.is_satisfied_by(&"john2@example.com".to_string()));
+        assert_eq!(format(Email).name(), "Email");
+    }
+}
@@ -0,0 +1,88 @@
+// Typo tolerant string matching, based on the Levenshtein distance.
+
+use crate::Specification;
+
+/// Satisfied by strings containing a substring within `max_distance` edits
+/// (insertions, deletions or substitutions of characters) of `needle`.
+pub fn fuzzy_contains(needle: impl Into<String>, max_distance: usize) -> FuzzyContains {
+    FuzzyContains {
+        needle: needle.into().chars().collect(),
+        max_distance,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FuzzyContains {
+    needle: Vec<char>,
+    max_distance: usize,
+}
+
+impl Specification<String> for FuzzyContains {
+    fn is_satisfied_by(&self, candidate: &String) -> bool {
+        substring_distance(&self.needle, candidate) <= self.max_distance
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+// The smallest Levenshtein distance between `needle` and any substring of `haystack`.
+// Like the plain distance, except a match may start and end anywhere in the haystack.
+fn substring_distance(needle: &[char], haystack: &str) -> usize {
+    // previous[i] is the distance of the first `i` characters of the needle,
+    // matched against a substring ending at the current haystack position.
+    let mut previous: Vec<usize> = (0..=needle.len()).collect();
+    let mut best = needle.len();
+    for c in haystack.chars() {
+        let mut current = vec![0; needle.len() + 1];
+        for (i, &n) in needle.iter().enumerate() {
+            let substitution = previous[i] + usize::from(n != c);
+            current[i + 1] = substitution.min(previous[i + 1] + 1).min(current[i] + 1);
+        }
+        best = best.min(current[needle.len()]);
+        previous = current;
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_contains_exact() {
+        let specification = fuzzy_contains("Python", 0);
+
+        assert!(specification.is_satisfied_by(&"Python".to_string()));
+        assert!(specification.is_satisfied_by(&"C++, Python, Go".to_string()));
+        assert!(!specification.is_satisfied_by(&"Pyhton".to_string()));
+    }
+
+    #[test]
+    fn test_fuzzy_contains_near() {
+        let specification = fuzzy_contains("Python", 2);
+
+        assert!(specification.is_satisfied_by(&"Pyhton".to_string()));
+        assert!(specification.is_satisfied_by(&"worked with Pythn".to_string()));
+        assert!(specification.is_satisfied_by(&"Pyton and Rust".to_string()));
+    }
+
+    #[test]
+    fn test_fuzzy_contains_far() {
+        let specification = fuzzy_contains("Python", 1);
+
+        assert!(!specification.is_satisfied_by(&"Rust".to_string()));
+        assert!(!specification.is_satisfied_by(&"Pyhton".to_string()));
+        assert!(!specification.is_satisfied_by(&String::new()));
+    }
+
+    #[test]
+    fn test_substring_distance() {
+        let needle: Vec<char> = "kitten".chars().collect();
+
+        assert_eq!(substring_distance(&needle, "sitting"), 2);
+        assert_eq!(substring_distance(&needle, "the kitten sat"), 0);
+        assert_eq!(substring_distance(&needle, ""), 6);
+        assert_eq!(substring_distance(&[], "anything"), 0);
+    }
+}
@@ -0,0 +1,476 @@
+// A hand written JSON value, to exchange explanations with front-ends and to validate
+// semi-structured candidates, without depending on a JSON library.
+
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use crate::Specification;
+
+/// A JSON document. Objects keep their keys in insertion order, so the output is stable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// The value of `key`, if this is an object having it.
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(fields) => fields
+                .iter()
+                .find(|(field, _)| field == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// The element at `index`, if this is an array long enough.
+    pub fn get_index(&self, index: usize) -> Option<&Self> {
+        match self {
+            Self::Array(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    /// The value at a dotted `path`, such as `address.lines.0`: each part is the key of an
+    /// object or the index of an array. An empty path is the value itself.
+    pub fn pointer(&self, path: &str) -> Option<&Self> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('.').try_fold(self, |value, part| match value {
+            Self::Object(..) => value.get(part),
+            Self::Array(..) => part.parse().ok().and_then(|index| value.get_index(index)),
+            _ => None,
+        })
+    }
+}
+
+/// Why a JSON document couldn't be parsed, the position counts characters from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "at {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl FromStr for JsonValue {
+    type Err = JsonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s.chars().collect(),
+            position: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("unexpected content after the value"));
+        }
+        Ok(value)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn value(&mut self) -> Result<JsonValue, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(JsonValue::String),
+            Some('t') => self.literal("true", JsonValue::Bool(true)),
+            Some('f') => self.literal("false", JsonValue::Bool(false)),
+            Some('n') => self.literal("null", JsonValue::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, JsonError> {
+        self.position += 1;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if !self.eat(':') {
+                return Err(self.error("expected `:`"));
+            }
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(JsonValue::Object(fields));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected `,` or `}`"));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, JsonError> {
+        self.position += 1;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(JsonValue::Array(elements));
+        }
+        loop {
+            elements.push(self.value()?);
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Ok(JsonValue::Array(elements));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected `,` or `]`"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.position += 1;
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let hex: String =
+                                self.chars.iter().skip(self.position + 1).take(4).collect();
+                            let escaped = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .filter(|_| hex.len() == 4)
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            self.position += 4;
+                            escaped
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.position += 1;
+                    value.push(escaped);
+                }
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.position += 1;
+        }
+        let number: String = self.chars[start..self.position].iter().collect();
+        number
+            .parse()
+            .map(JsonValue::Number)
+            .map_err(|_| JsonError {
+                position: start,
+                message: format!("invalid number `{}`", number),
+            })
+    }
+
+    fn literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, JsonError> {
+        if literal
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.position + i) == Some(&c))
+        {
+            self.position += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.position += 1;
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn error(&self, message: &str) -> JsonError {
+        JsonError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Satisfied by JSON candidates having a value at the dotted `path`, see
+/// [`JsonValue::pointer`], that satisfies `inner`. A path that goes missing,
+/// or runs into a value of the wrong type, e.g. a key into an array, is rejected.
+pub fn has_field<S>(path: impl Into<String>, inner: S) -> HasField<S> {
+    HasField {
+        path: path.into(),
+        inner,
+    }
+}
+
+#[derive(Debug)]
+pub struct HasField<S> {
+    path: String,
+    inner: S,
+}
+
+impl<S: Specification<JsonValue>> Specification<JsonValue> for HasField<S> {
+    fn is_satisfied_by(&self, candidate: &JsonValue) -> bool {
+        candidate
+            .pointer(&self.path)
+            .is_some_and(|value| self.inner.is_satisfied_by(value))
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.inner.eq_by_debug()
+    }
+}
+
+// Writes the compact form, without any whitespace. Numbers JSON can't represent are `null`.
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Number(value) if value.is_finite() => write!(f, "{}", value),
+            Self::Number(_) => write!(f, "null"),
+            Self::String(value) => write_string(value, f),
+            Self::Array(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(key, f)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(s: &str, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> JsonValue {
+        s.parse().unwrap()
+    }
+
+    fn person() -> JsonValue {
+        parse(
+            r#"{
+                "name": "Ada",
+                "age": 36,
+                "address": {"city": "London", "lines": ["12 St James's Square"]},
+                "tags": []
+            }"#,
+        )
+    }
+
+    #[derive(Debug)]
+    struct Is(fn(&JsonValue) -> bool);
+
+    impl Specification<JsonValue> for Is {
+        fn is_satisfied_by(&self, candidate: &JsonValue) -> bool {
+            (self.0)(candidate)
+        }
+    }
+
+    const ANYTHING: Is = Is(|_| true);
+    const NUMBER: Is = Is(|value| matches!(value, JsonValue::Number(..)));
+
+    #[test]
+    fn test_has_field_present() {
+        let specification = has_field(
+            "age",
+            Is(|age| matches!(age, JsonValue::Number(age) if *age >= 18.0)),
+        )
+        .and(has_field(
+            "address.city",
+            Is(|city| *city == JsonValue::String("London".to_string())),
+        ))
+        .and(has_field(
+            "address.lines.0",
+            Is(|line| matches!(line, JsonValue::String(..))),
+        ));
+
+        assert!(specification.is_satisfied_by(&person()));
+        assert!(!specification.is_satisfied_by(&parse(r#"{"age": 12}"#)));
+    }
+
+    #[test]
+    fn test_has_field_missing() {
+        assert!(!has_field("email", ANYTHING).is_satisfied_by(&person()));
+        assert!(!has_field("address.zip", ANYTHING).is_satisfied_by(&person()));
+        assert!(!has_field("address.lines.1", ANYTHING).is_satisfied_by(&person()));
+        assert!(!has_field("tags.0", ANYTHING).is_satisfied_by(&person()));
+        assert!(has_field("", ANYTHING).is_satisfied_by(&person()));
+    }
+
+    #[test]
+    fn test_has_field_wrong_type() {
+        assert!(!has_field("name", NUMBER).is_satisfied_by(&person()));
+        assert!(!has_field("name.first", ANYTHING).is_satisfied_by(&person()));
+        assert!(!has_field("address.lines.first", ANYTHING).is_satisfied_by(&person()));
+        assert!(has_field("age", NUMBER).is_satisfied_by(&person()));
+    }
+
+    #[test]
+    fn test_parse() {
+        let value = parse(r#" {"a": [1, -2.5e1, true, false, null], "b\n\u0041": {}} "#);
+
+        assert_eq!(
+            value,
+            JsonValue::Object(vec![
+                (
+                    "a".to_string(),
+                    JsonValue::Array(vec![
+                        JsonValue::Number(1.0),
+                        JsonValue::Number(-25.0),
+                        JsonValue::Bool(true),
+                        JsonValue::Bool(false),
+                        JsonValue::Null,
+                    ]),
+                ),
+                ("b\nA".to_string(), JsonValue::Object(vec![])),
+            ])
+        );
+        assert_eq!(parse(&value.to_string()), value);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |s: &str| s.parse::<JsonValue>().unwrap_err();
+
+        assert_eq!(error(r#"{"a" 1}"#).message, "expected `:`");
+        assert_eq!(error("[1, ").message, "unexpected end of input");
+        assert_eq!(error("[1, 2").message, "expected `,` or `]`");
+        assert_eq!(error("[1] 2").position, 4);
+        assert_eq!(error("tru").message, "expected a value");
+        assert_eq!(error(r#""abc"#).message, "unterminated string");
+        assert_eq!(error("1.2.3").message, "invalid number `1.2.3`");
+    }
+
+    #[test]
+    fn test_to_string() {
+        let value = JsonValue::Object(vec![
+            (
+                "name".to_string(),
+                JsonValue::String(r#"a "b"\c"#.to_string()),
+            ),
+            (
+                "values".to_string(),
+                JsonValue::Array(vec![
+                    JsonValue::Number(1.0),
+                    JsonValue::Number(2.5),
+                    JsonValue::Number(f64::NAN),
+                    JsonValue::Bool(true),
+                    JsonValue::Null,
+                ]),
+            ),
+            ("line".to_string(), JsonValue::String("a\nb".to_string())),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"name":"a \"b\"\\c","values":[1,2.5,null,true,null],"line":"a\nb"}"#
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let value = JsonValue::Object(vec![(
+            "items".to_string(),
+            JsonValue::Array(vec![JsonValue::Bool(false)]),
+        )]);
+
+        assert_eq!(
+            value.get("items").and_then(|items| items.get_index(0)),
+            Some(&JsonValue::Bool(false))
+        );
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(value.get_index(0), None);
+        assert_eq!(JsonValue::Null.get("items"), None);
+    }
+}
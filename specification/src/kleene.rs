@@ -0,0 +1,215 @@
+// Three-valued (Kleene) logic, for candidates whose data might be missing.
+
+use crate::SpecificationCompositions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Truth {
+    True,
+    False,
+    Unknown,
+}
+
+impl From<bool> for Truth {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::True
+        } else {
+            Self::False
+        }
+    }
+}
+
+/// A leaf specification that can tell when it can't decide about a candidate.
+///
+/// Implementors also need to override [`crate::Specification::as_kleene`] to return themselves,
+/// leaves without it are evaluated as plain booleans.
+pub trait KleeneSpecification<T> {
+    fn evaluate(&self, candidate: &T) -> Truth;
+}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Evaluates with Kleene's strong logic: `Unknown` propagates unless the other children
+    /// decide anyway, like `false and unknown` being `false`.
+    pub fn evaluate_kleene(&self, candidate: &T) -> Truth {
+        match self {
+            Self::Specification(f) => match f.as_kleene() {
+                Some(kleene) => kleene.evaluate(candidate),
+                None => f.is_satisfied_by(candidate).into(),
+            },
+            Self::And(specifications) => {
+                let mut result = Truth::True;
+                for specification in specifications {
+                    match specification.evaluate_kleene(candidate) {
+                        Truth::False => return Truth::False,
+                        Truth::Unknown => result = Truth::Unknown,
+                        Truth::True => {}
+                    }
+                }
+                result
+            }
+            Self::Or(specifications) => {
+                let mut result = Truth::False;
+                for specification in specifications {
+                    match specification.evaluate_kleene(candidate) {
+                        Truth::True => return Truth::True,
+                        Truth::Unknown => result = Truth::Unknown,
+                        Truth::False => {}
+                    }
+                }
+                result
+            }
+            Self::Xor(specifications) => {
+                let mut satisfied = 0;
+                for specification in specifications {
+                    match specification.evaluate_kleene(candidate) {
+                        Truth::Unknown => return Truth::Unknown,
+                        Truth::True => satisfied += 1,
+                        Truth::False => {}
+                    }
+                }
+                (satisfied % 2 == 1).into()
+            }
+            Self::Invert(specification) => match specification.evaluate_kleene(candidate) {
+                Truth::True => Truth::False,
+                Truth::False => Truth::True,
+                Truth::Unknown => Truth::Unknown,
+            },
+            Self::True => Truth::True,
+            Self::False => Truth::False,
+            // Decided only when the unknown children can't change the outcome either way.
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let (mut certain, mut possible) = (0.0, 0.0);
+                for (weight, specification) in specifications {
+                    match specification.evaluate_kleene(candidate) {
+                        Truth::True => {
+                            certain += weight;
+                            possible += weight;
+                        }
+                        Truth::Unknown => possible += weight,
+                        Truth::False => {}
+                    }
+                }
+                if certain >= *threshold {
+                    Truth::True
+                } else if possible < *threshold {
+                    Truth::False
+                } else {
+                    Truth::Unknown
+                }
+            }
+            Self::MinSatisfied(count, specifications) => {
+                let (certain, possible) = Self::kleene_counts(specifications, candidate);
+                if certain >= *count {
+                    Truth::True
+                } else if possible < *count {
+                    Truth::False
+                } else {
+                    Truth::Unknown
+                }
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                let (certain, possible) = Self::kleene_counts(specifications, candidate);
+                if possible <= *count {
+                    Truth::True
+                } else if certain > *count {
+                    Truth::False
+                } else {
+                    Truth::Unknown
+                }
+            }
+        }
+    }
+
+    // The number of children certainly satisfied, and the number possibly satisfied,
+    // counting the unknown ones too.
+    fn kleene_counts(specifications: &[Self], candidate: &T) -> (usize, usize) {
+        let (mut certain, mut possible) = (0, 0);
+        for specification in specifications {
+            match specification.evaluate_kleene(candidate) {
+                Truth::True => {
+                    certain += 1;
+                    possible += 1;
+                }
+                Truth::Unknown => possible += 1,
+                Truth::False => {}
+            }
+        }
+        (certain, possible)
+    }
+
+    /// Boolean evaluation resolving an `Unknown` outcome to `default_for_unknown`, so callers
+    /// choose between optimistic and pessimistic handling of missing data.
+    ///
+    /// The default applies to the outcome of the whole composition, not to each leaf,
+    /// so `not unknown` is resolved to the default as well.
+    pub fn is_satisfied_by_or(&self, candidate: &T, default_for_unknown: bool) -> bool {
+        match self.evaluate_kleene(candidate) {
+            Truth::True => true,
+            Truth::False => false,
+            Truth::Unknown => default_for_unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Specification;
+
+    #[derive(Debug)]
+    struct AgeAbove(i32);
+
+    impl Specification<Option<i32>> for AgeAbove {
+        fn is_satisfied_by(&self, candidate: &Option<i32>) -> bool {
+            candidate.is_some_and(|age| age > self.0)
+        }
+        fn as_kleene(&self) -> Option<&dyn KleeneSpecification<Option<i32>>> {
+            Some(self)
+        }
+    }
+
+    impl KleeneSpecification<Option<i32>> for AgeAbove {
+        fn evaluate(&self, candidate: &Option<i32>) -> Truth {
+            match candidate {
+                Some(age) => (*age > self.0).into(),
+                None => Truth::Unknown,
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Known;
+
+    impl Specification<Option<i32>> for Known {
+        fn is_satisfied_by(&self, candidate: &Option<i32>) -> bool {
+            candidate.is_some()
+        }
+    }
+
+    #[test]
+    fn test_evaluate_kleene() {
+        let specification = AgeAbove(18).and(AgeAbove(65).invert());
+
+        assert_eq!(specification.evaluate_kleene(&Some(30)), Truth::True);
+        assert_eq!(specification.evaluate_kleene(&Some(70)), Truth::False);
+        assert_eq!(specification.evaluate_kleene(&None), Truth::Unknown);
+    }
+
+    #[test]
+    fn test_evaluate_kleene_decided_despite_unknown() {
+        let specification = AgeAbove(18).or(Known.invert());
+
+        assert_eq!(specification.evaluate_kleene(&None), Truth::True);
+        assert_eq!(AgeAbove(18).and(Known).evaluate_kleene(&None), Truth::False);
+    }
+
+    #[test]
+    fn test_is_satisfied_by_or() {
+        let specification = AgeAbove(18).and(AgeAbove(65).invert());
+
+        assert!(specification.is_satisfied_by_or(&None, true));
+        assert!(!specification.is_satisfied_by_or(&None, false));
+        assert!(specification.is_satisfied_by_or(&Some(30), false));
+        assert!(!specification.is_satisfied_by_or(&Some(70), true));
+    }
+}
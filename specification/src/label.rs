@@ -0,0 +1,127 @@
+// Groups of specifications named after the requirement they stand for,
+// so failures are explained by the requirement instead of its leaves.
+
+use crate::{Specification, SpecificationCompositions};
+
+/// Gives `specification` the name `label`. The group evaluates exactly like `specification`,
+/// but it is a single leaf of the composition it is part of: when it fails, the unsatisfied
+/// remainder and the explanations built from it show the label, not the leaves inside.
+pub fn labeled<T: std::fmt::Debug + 'static>(
+    label: impl Into<String>,
+    specification: impl Specification<T> + 'static,
+) -> Labeled<T> {
+    Labeled {
+        label: label.into(),
+        specification: specification.composite(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Labeled<T: std::fmt::Debug> {
+    label: String,
+    specification: SpecificationCompositions<T>,
+}
+
+impl<T: std::fmt::Debug> Labeled<T> {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The labeled group, e.g. to explain which of its leaves failed.
+    pub fn specification(&self) -> &SpecificationCompositions<T> {
+        &self.specification
+    }
+}
+
+impl<T: std::fmt::Debug + 'static> Specification<T> for Labeled<T> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.specification.is_satisfied_by(candidate)
+    }
+    fn name(&self) -> String {
+        self.label.clone()
+    }
+    fn cost(&self) -> u32 {
+        self.specification.cost()
+    }
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+}
+
+impl<T: std::fmt::Debug + 'static> SpecificationCompositions<T> {
+    /// `self and specification`, the latter as a group named `label`, see [`labeled`].
+    pub fn labeled_and(
+        self,
+        label: impl Into<String>,
+        specification: impl Specification<T> + 'static,
+    ) -> Self {
+        self.and(labeled(label, specification))
+    }
+
+    /// `self or specification`, the latter as a group named `label`, see [`labeled`].
+    pub fn labeled_or(
+        self,
+        label: impl Into<String>,
+        specification: impl Specification<T> + 'static,
+    ) -> Self {
+        self.or(labeled(label, specification))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_labeled_and() {
+        let specification = GreaterThan { value: -100 }.composite().labeled_and(
+            "in range",
+            GreaterThan { value: 5 }.and(LessThan { value: 10 }),
+        );
+
+        assert!(specification.is_satisfied_by(&7));
+        assert!(!specification.is_satisfied_by(&12));
+        assert_eq!(specification.satisfied_summary(&12), "FAIL: in range");
+        assert_eq!(
+            specification.satisfied_summary(&-200),
+            "FAIL: GreaterThan, in range"
+        );
+    }
+
+    #[test]
+    fn test_labeled_or() {
+        let specification = Zero {}
+            .composite()
+            .labeled_or("above 5", GreaterThan { value: 5 });
+
+        assert!(specification.is_satisfied_by(&0));
+        assert!(specification.is_satisfied_by(&6));
+        assert_eq!(specification.satisfied_summary(&3), "FAIL: Zero, above 5");
+    }
+
+    #[test]
+    fn test_labeled_group() {
+        let specification = labeled(
+            "in range",
+            GreaterThan { value: 5 }.and(LessThan { value: 10 }),
+        );
+        let reminder = specification
+            .composite()
+            .reminder_unsatisfied_by(&12)
+            .unwrap();
+
+        let SpecificationCompositions::Specification(leaf) = reminder else {
+            panic!("expected a leaf");
+        };
+        let group = leaf
+            .as_any()
+            .and_then(|any| any.downcast_ref::<Labeled<i32>>())
+            .unwrap();
+        assert_eq!(group.label(), "in range");
+        assert_eq!(
+            group.specification().satisfied_summary(&12),
+            "FAIL: LessThan"
+        );
+    }
+}
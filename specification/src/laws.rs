@@ -0,0 +1,104 @@
+// Checking the laws of boolean algebra on concrete specifications, e.g. to guard
+// transformations rewriting trees against regressions.
+
+use crate::{Specification, SpecificationCompositions};
+
+/// Which laws held for every sample, see [`check_laws`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Laws {
+    pub commutativity: bool,
+    pub associativity: bool,
+    pub distributivity: bool,
+    pub de_morgan: bool,
+    pub double_negation: bool,
+}
+
+impl Laws {
+    pub fn all(&self) -> bool {
+        self.commutativity
+            && self.associativity
+            && self.distributivity
+            && self.de_morgan
+            && self.double_negation
+    }
+}
+
+/// Evaluates both sides of each law, for `And` and `Or` alike, built from `a`, `b` and `c`
+/// against every sample. A law holds when both sides agree on all the samples.
+pub fn check_laws<T: std::fmt::Debug>(
+    a: &SpecificationCompositions<T>,
+    b: &SpecificationCompositions<T>,
+    c: &SpecificationCompositions<T>,
+    samples: &[T],
+) -> Laws {
+    use SpecificationCompositions::{And, Invert, Or};
+
+    let not = |x: &SpecificationCompositions<T>| Invert(Box::new(x.clone()));
+    let and = |x: &SpecificationCompositions<T>, y: &SpecificationCompositions<T>| {
+        And(vec![x.clone(), y.clone()])
+    };
+    let or = |x: &SpecificationCompositions<T>, y: &SpecificationCompositions<T>| {
+        Or(vec![x.clone(), y.clone()])
+    };
+    let equivalent = |pairs: &[(SpecificationCompositions<T>, SpecificationCompositions<T>)]| {
+        pairs.iter().all(|(left, right)| {
+            samples
+                .iter()
+                .all(|sample| left.is_satisfied_by(sample) == right.is_satisfied_by(sample))
+        })
+    };
+
+    Laws {
+        commutativity: equivalent(&[(and(a, b), and(b, a)), (or(a, b), or(b, a))]),
+        associativity: equivalent(&[
+            (and(&and(a, b), c), and(a, &and(b, c))),
+            (or(&or(a, b), c), or(a, &or(b, c))),
+        ]),
+        distributivity: equivalent(&[
+            (and(a, &or(b, c)), or(&and(a, b), &and(a, c))),
+            (or(a, &and(b, c)), and(&or(a, b), &or(a, c))),
+        ]),
+        de_morgan: equivalent(&[
+            (not(&and(a, b)), or(&not(a), &not(b))),
+            (not(&or(a, b)), and(&not(a), &not(b))),
+        ]),
+        double_negation: equivalent(&[(not(&not(a)), a.clone())]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_check_laws() {
+        let a = GreaterThan { value: 5 }.composite();
+        let b = LessThan { value: 10 }.or(Zero {});
+        let c = Zero {}.invert().xor(GreaterThan { value: 20 });
+        let samples: Vec<i32> = (-5..30).collect();
+
+        let laws = check_laws(&a, &b, &c, &samples);
+
+        assert!(laws.all(), "{:?}", laws);
+    }
+
+    #[test]
+    fn test_check_laws_on_transformed() {
+        let a = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .invert();
+        let b = Zero {}.or(GreaterThan { value: 20 }).invert();
+        let c = LessThan { value: 0 }.composite();
+        let samples: Vec<i32> = (-5..30).collect();
+
+        let laws = check_laws(
+            &a.to_nnf(),
+            &b.canonicalize().optimize(),
+            &c.intern(),
+            &samples,
+        );
+
+        assert!(laws.all(), "{:?}", laws);
+    }
+}
@@ -1,6 +1,47 @@
+use std::borrow::Borrow;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+mod evaluate;
+mod explain;
+#[cfg(test)]
+mod fixtures;
+mod transform;
+
+pub mod adapters;
+pub mod analysis;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod bytes;
+pub mod cache;
+pub mod combine;
+pub mod comparison;
+pub mod dataset;
+#[cfg(feature = "format")]
+pub mod format;
+#[cfg(feature = "fuzzy")]
+pub mod fuzzy;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod kleene;
+pub mod label;
+pub mod laws;
+pub mod parse;
+pub mod record;
+#[cfg(feature = "regex")]
+pub mod regex;
+pub mod registry;
+pub mod sequence;
+pub mod sql;
+pub mod sync;
+pub mod tiered;
+pub mod trace;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+pub use evaluate::{BatchEvaluator, DepthExceeded, EvalPolicy, EvalStats};
+
 pub trait Specification<T: std::fmt::Debug>: std::fmt::Debug {
     fn is_satisfied_by(&self, candidate: &T) -> bool;
 
@@ -8,36 +49,25 @@ pub trait Specification<T: std::fmt::Debug>: std::fmt::Debug {
     where
         Self: 'static + Sized,
     {
-        SpecificationCompositions::And(vec![
-            SpecificationCompositions::Specification(Arc::new(self)),
-            SpecificationCompositions::Specification(Arc::new(other)),
-        ])
+        self.composite().and(other)
     }
     fn or(self, other: impl Specification<T> + 'static) -> SpecificationCompositions<T>
     where
         Self: 'static + Sized,
     {
-        SpecificationCompositions::Or(vec![
-            SpecificationCompositions::Specification(Arc::new(self)),
-            SpecificationCompositions::Specification(Arc::new(other)),
-        ])
+        self.composite().or(other)
     }
     fn invert(self) -> SpecificationCompositions<T>
     where
         Self: 'static + Sized,
     {
-        SpecificationCompositions::Invert(Box::new(SpecificationCompositions::Specification(
-            Arc::new(self),
-        )))
+        SpecificationCompositions::Invert(Box::new(self.composite()))
     }
     fn xor(self, other: impl Specification<T> + 'static) -> SpecificationCompositions<T>
     where
         Self: 'static + Sized,
     {
-        SpecificationCompositions::Xor(vec![
-            SpecificationCompositions::Specification(Arc::new(self)),
-            SpecificationCompositions::Specification(Arc::new(other)),
-        ])
+        SpecificationCompositions::Xor(vec![self.composite(), other.composite()])
     }
     fn composite(self) -> SpecificationCompositions<T>
     where
@@ -45,9 +75,114 @@ pub trait Specification<T: std::fmt::Debug>: std::fmt::Debug {
     {
         SpecificationCompositions::Specification(Arc::new(self))
     }
+    /// Human readable name of the specification, by default its type name without the path.
+    fn name(&self) -> String {
+        let type_name = std::any::type_name::<Self>();
+        let type_name = type_name.split('<').next().unwrap_or(type_name);
+        type_name
+            .rsplit("::")
+            .next()
+            .unwrap_or(type_name)
+            .to_string()
+    }
+    /// Relative evaluation cost, used by [`SpecificationCompositions::optimize`]
+    /// to run cheap checks first.
+    fn cost(&self) -> u32 {
+        1
+    }
+    /// Weight of the specification when choosing the main reason of a failure,
+    /// see [`SpecificationCompositions::primary_failure`].
+    fn importance(&self) -> u32 {
+        1
+    }
+    /// Whether the `Debug` output tells everything the leaf checks, so leaves of the same type
+    /// printing the same are equal in `==` and hashed alike, even if built separately.
+    /// Leaves are otherwise only equal to themselves: keep the default for leaves holding
+    /// closures or anything else `Debug` can't show.
+    fn eq_by_debug(&self) -> bool {
+        false
+    }
+    /// The leaf itself as `Any`, if it allows downcasting, so transformations can recognize
+    /// leaves of known types, see [`SpecificationCompositions::simplify_numeric`].
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        None
+    }
+    /// The three-valued evaluation of the leaf, if it has one,
+    /// see [`SpecificationCompositions::is_satisfied_by_or`].
+    fn as_kleene(&self) -> Option<&dyn kleene::KleeneSpecification<T>> {
+        None
+    }
+    /// The SQL translation of the leaf, if it has one, see [`SpecificationCompositions::to_sql`].
+    fn as_to_sql(&self) -> Option<&dyn sql::ToSql> {
+        None
+    }
+    /// The first candidate of `domain` satisfying the specification, if there is any.
+    /// A pragmatic way to check that a rule is achievable over a known domain.
+    fn find_satisfying<I: IntoIterator<Item = T>>(&self, domain: I) -> Option<T>
+    where
+        Self: Sized,
+    {
+        domain
+            .into_iter()
+            .find(|candidate| self.is_satisfied_by(candidate))
+    }
+    /// Splits `items` into references to the satisfying ones and to the others,
+    /// preserving their order, so nothing is cloned.
+    fn partition_ref<'a>(&self, items: &'a [T]) -> (Vec<&'a T>, Vec<&'a T>)
+    where
+        Self: Sized,
+    {
+        items.iter().partition(|item| self.is_satisfied_by(item))
+    }
+    /// References to the satisfying `items`, in their order.
+    fn filter_ref<'a>(&self, items: &'a [T]) -> Vec<&'a T>
+    where
+        Self: Sized,
+    {
+        items
+            .iter()
+            .filter(|item| self.is_satisfied_by(item))
+            .collect()
+    }
+    /// How many of the `items` satisfy the specification.
+    fn count_satisfied_ref(&self, items: &[T]) -> usize
+    where
+        Self: Sized,
+    {
+        items
+            .iter()
+            .filter(|item| self.is_satisfied_by(item))
+            .count()
+    }
+    /// Whether every item satisfies the specification, accepting owned and borrowed items alike.
+    fn satisfied_by_all<B: Borrow<T>>(&self, items: impl IntoIterator<Item = B>) -> bool
+    where
+        Self: Sized,
+    {
+        items
+            .into_iter()
+            .all(|item| self.is_satisfied_by(item.borrow()))
+    }
+    /// Converts `value` into a candidate and evaluates it, e.g. a `&str` for a `String` candidate.
+    fn satisfied_by_into<V: Into<T>>(&self, value: V) -> bool
+    where
+        Self: Sized,
+    {
+        self.is_satisfied_by(&value.into())
+    }
+    /// Builder-time conditional chaining, see [`SpecificationCompositions::and_then`].
+    fn and_then<F>(self, f: F) -> SpecificationCompositions<T>
+    where
+        Self: 'static + Sized,
+        T: 'static,
+        F: FnOnce(&SpecificationCompositions<T>) -> SpecificationCompositions<T>,
+    {
+        self.composite().and_then(f)
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Evaluating a composition doesn't recurse, so trees of any depth can be evaluated; cloning,
+/// comparing, hashing, formatting and dropping still recurse once per level of nesting.
 pub enum SpecificationCompositions<T: std::fmt::Debug> {
     Specification(Arc<dyn Specification<T>>),
     And(Vec<SpecificationCompositions<T>>),
@@ -56,35 +191,115 @@ pub enum SpecificationCompositions<T: std::fmt::Debug> {
     Invert(Box<SpecificationCompositions<T>>),
     True,
     False,
+    /// Satisfied when the weights of the satisfied children add up to at least the threshold.
+    WeightedAtLeast(f64, Vec<(f64, SpecificationCompositions<T>)>),
+    /// Satisfied when at least the given number of its children are.
+    MinSatisfied(usize, Vec<SpecificationCompositions<T>>),
+    /// Satisfied when at most the given number of its children are.
+    MaxSatisfied(usize, Vec<SpecificationCompositions<T>>),
+}
+
+// Cloning only clones the `Arc`s of the leaves, so it doesn't need `T: Clone` as derive would.
+impl<T: std::fmt::Debug> Clone for SpecificationCompositions<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Specification(f) => Self::Specification(f.clone()),
+            Self::And(specifications) => Self::And(specifications.clone()),
+            Self::Or(specifications) => Self::Or(specifications.clone()),
+            Self::Xor(specifications) => Self::Xor(specifications.clone()),
+            Self::Invert(specification) => Self::Invert(specification.clone()),
+            Self::True => Self::True,
+            Self::False => Self::False,
+            Self::WeightedAtLeast(threshold, specifications) => {
+                Self::WeightedAtLeast(*threshold, specifications.clone())
+            }
+            Self::MinSatisfied(count, specifications) => {
+                Self::MinSatisfied(*count, specifications.clone())
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                Self::MaxSatisfied(*count, specifications.clone())
+            }
+        }
+    }
 }
 
 impl<T: std::fmt::Debug> Specification<T> for SpecificationCompositions<T> {
+    // Walks the tree with an explicit stack rather than recursing, so arbitrarily deep
+    // compositions can't overflow the call stack. `And` and `Or` still short-circuit.
     fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.evaluate_iteratively(None, &mut |f| f.is_satisfied_by(candidate))
+            .unwrap_or_else(|_| unreachable!("evaluation without a depth limit"))
+    }
+
+    fn name(&self) -> String {
         match self {
-            Self::Specification(f) => f.is_satisfied_by(candidate),
-            Self::And(specifications) => specifications
-                .iter()
-                .all(|specification| specification.is_satisfied_by(candidate)),
-            Self::Or(specifications) => specifications
-                .iter()
-                .any(|specification| specification.is_satisfied_by(candidate)),
-            Self::Invert(specification) => !specification.is_satisfied_by(candidate),
-            Self::Xor(specifications) => {
-                specifications
-                    .iter()
-                    .filter(|specification| specification.is_satisfied_by(candidate))
-                    .count()
-                    == 1
-            }
-            Self::True => true,
-            Self::False => false,
+            Self::Specification(f) => f.name(),
+            Self::And(..) => "and".to_string(),
+            Self::Or(..) => "or".to_string(),
+            Self::Xor(..) => "xor".to_string(),
+            Self::Invert(..) => "not".to_string(),
+            Self::True => "true".to_string(),
+            Self::False => "false".to_string(),
+            Self::WeightedAtLeast(..) => "weighted_at_least".to_string(),
+            Self::MinSatisfied(..) => "min_satisfied".to_string(),
+            Self::MaxSatisfied(..) => "max_satisfied".to_string(),
         }
     }
+
+    fn cost(&self) -> u32 {
+        match self {
+            Self::Specification(f) => f.cost(),
+            _ => self.children().into_iter().map(Specification::cost).sum(),
+        }
+    }
+
+    // A composition is already a composition, don't hide it behind another `Arc`.
+    fn composite(self) -> SpecificationCompositions<T>
+    where
+        Self: 'static + Sized,
+    {
+        self
+    }
+}
+
+// An array of specifications is a conjunction, an empty array is vacuously satisfied.
+impl<T: std::fmt::Debug, S: Specification<T>, const N: usize> Specification<T> for [S; N] {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.iter()
+            .all(|specification| specification.is_satisfied_by(candidate))
+    }
 }
 
+// Tuples combine different specification types in a conjunction without boxing them.
+macro_rules! tuple_specification {
+    ($($name:ident: $index:tt),+) => {
+        impl<T: std::fmt::Debug, $($name: Specification<T>),+> Specification<T> for ($($name,)+) {
+            fn is_satisfied_by(&self, candidate: &T) -> bool {
+                $(self.$index.is_satisfied_by(candidate))&&+
+            }
+        }
+    };
+}
+
+tuple_specification!(A: 0, B: 1);
+tuple_specification!(A: 0, B: 1, C: 2);
+tuple_specification!(A: 0, B: 1, C: 2, D: 3);
+tuple_specification!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_specification!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+
 impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Conjunction with `other`, a constant side decides it right away:
+    /// `False` is returned as is, `True` leaves the other side.
     pub fn and(self, other: impl Specification<T> + 'static) -> Self {
         let other = other.composite();
+        match (self, other) {
+            (Self::False, _) | (_, Self::False) => Self::False,
+            (Self::True, other) => other,
+            (this, Self::True) => this,
+            (this, other) => this.and_composition(other),
+        }
+    }
+    fn and_composition(self, other: Self) -> Self {
         match self {
             Self::And(mut specifications) => {
                 match other {
@@ -98,8 +313,18 @@ impl<T: std::fmt::Debug> SpecificationCompositions<T> {
             _ => Self::And(vec![self, other]),
         }
     }
+    /// Disjunction with `other`, a constant side decides it right away:
+    /// `True` is returned as is, `False` leaves the other side.
     pub fn or(self, other: impl Specification<T> + 'static) -> Self {
         let other = other.composite();
+        match (self, other) {
+            (Self::True, _) | (_, Self::True) => Self::True,
+            (Self::False, other) => other,
+            (this, Self::False) => this,
+            (this, other) => this.or_composition(other),
+        }
+    }
+    fn or_composition(self, other: Self) -> Self {
         match self {
             Self::Or(mut specifications) => {
                 match other {
@@ -136,6 +361,64 @@ impl<T: std::fmt::Debug> SpecificationCompositions<T> {
         self
     }
 
+    /// Conditionally extends the specification while it is being built.
+    ///
+    /// `f` receives the composition built so far and returns a further requirement
+    /// to be and-ed to it, or `True` if nothing else is needed. This is decided once,
+    /// at construction time; it is not evaluated per candidate.
+    pub fn and_then<F>(self, f: F) -> Self
+    where
+        T: 'static,
+        F: FnOnce(&Self) -> Self,
+    {
+        match f(&self) {
+            Self::True => self,
+            extra => self.and(extra),
+        }
+    }
+
+    pub(crate) fn children(&self) -> Vec<&Self> {
+        match self {
+            Self::And(specifications)
+            | Self::Or(specifications)
+            | Self::Xor(specifications)
+            | Self::MinSatisfied(_, specifications)
+            | Self::MaxSatisfied(_, specifications) => specifications.iter().collect(),
+            Self::Invert(specification) => vec![specification],
+            Self::WeightedAtLeast(_, specifications) => specifications
+                .iter()
+                .map(|(_, specification)| specification)
+                .collect(),
+            Self::Specification(..) | Self::True | Self::False => vec![],
+        }
+    }
+
+    pub(crate) fn for_each_leaf(&self, f: &mut impl FnMut(&Arc<dyn Specification<T>>)) {
+        match self {
+            Self::Specification(specification) => f(specification),
+            _ => {
+                for specification in self.children() {
+                    specification.for_each_leaf(f);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn satisfied_weight(specifications: &[(f64, Self)], candidate: &T) -> f64 {
+        specifications
+            .iter()
+            .filter(|(_, specification)| specification.is_satisfied_by(candidate))
+            .map(|(weight, _)| weight)
+            .sum()
+    }
+
+    pub(crate) fn satisfied_count(specifications: &[Self], candidate: &T) -> usize {
+        specifications
+            .iter()
+            .filter(|specification| specification.is_satisfied_by(candidate))
+            .count()
+    }
+
     pub fn reminder_unsatisfied_by(&self, candidate: &T) -> Option<Self> {
         match self {
             Self::And(specifications) => {
@@ -155,43 +438,104 @@ impl<T: std::fmt::Debug> SpecificationCompositions<T> {
                 }
                 Some(Self::And(unsatisfied))
             }
+            // An Or only fails when every one of its children fails,
+            // so its reminder is what each of the alternatives is missing.
             Self::Or(specifications) => {
-                let mut unsatisfied = Vec::new();
-                for specification in specifications {
-                    if !specification.is_satisfied_by(candidate) {
-                        if let Some(reminder) = specification.reminder_unsatisfied_by(candidate) {
-                            unsatisfied.push(reminder);
-                        }
-                    }
-                }
-                if unsatisfied.is_empty() {
+                if self.is_satisfied_by(candidate) {
                     return None;
                 }
+                let mut unsatisfied: Vec<Self> = specifications
+                    .iter()
+                    .map(|specification| {
+                        specification
+                            .reminder_unsatisfied_by(candidate)
+                            .unwrap_or_else(|| specification.clone())
+                    })
+                    .collect();
                 if unsatisfied.len() == 1 {
                     return Some(unsatisfied.remove(0));
                 }
                 Some(Self::Or(unsatisfied))
             }
             Self::Invert(specification) => specification.reminder_unsatisfied_by(candidate),
+            // A failing Xor had an even number of its children held.
+            // If none held, the reminder is what each of them is missing,
+            // otherwise it is the children that held, as they cancel each other out.
             Self::Xor(specifications) => {
-                let mut unsatisfied = Vec::new();
-                for specification in specifications {
-                    if !specification.is_satisfied_by(candidate) {
-                        if let Some(reminder) = specification.reminder_unsatisfied_by(candidate) {
-                            unsatisfied.push(reminder);
-                        }
-                    }
-                }
-                if unsatisfied.is_empty() {
+                if self.is_satisfied_by(candidate) {
                     return None;
                 }
-                if unsatisfied.len() == 1 {
-                    return Some(unsatisfied.remove(0));
+                let (satisfied, unsatisfied): (Vec<_>, Vec<_>) = specifications
+                    .iter()
+                    .partition(|specification| specification.is_satisfied_by(candidate));
+                if satisfied.is_empty() {
+                    let mut unsatisfied: Vec<Self> = unsatisfied
+                        .into_iter()
+                        .map(|specification| {
+                            specification
+                                .reminder_unsatisfied_by(candidate)
+                                .unwrap_or_else(|| (*specification).clone())
+                        })
+                        .collect();
+                    if unsatisfied.len() == 1 {
+                        return Some(unsatisfied.remove(0));
+                    }
+                    return Some(Self::Xor(unsatisfied));
                 }
-                Some(Self::Xor(unsatisfied))
+                Some(Self::Xor(satisfied.into_iter().cloned().collect()))
             }
             Self::True => None,
             Self::False => None,
+            // The reminder asks for the missing weight from the children that didn't hold.
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let satisfied_weight = Self::satisfied_weight(specifications, candidate);
+                if satisfied_weight >= *threshold {
+                    return None;
+                }
+                let unsatisfied = specifications
+                    .iter()
+                    .filter(|(_, specification)| !specification.is_satisfied_by(candidate))
+                    .map(|(weight, specification)| {
+                        let reminder = specification
+                            .reminder_unsatisfied_by(candidate)
+                            .unwrap_or_else(|| specification.clone());
+                        (*weight, reminder)
+                    })
+                    .collect();
+                Some(Self::WeightedAtLeast(
+                    threshold - satisfied_weight,
+                    unsatisfied,
+                ))
+            }
+            // Like the weighted threshold, the missing count from the children that didn't hold.
+            Self::MinSatisfied(count, specifications) => {
+                let satisfied_count = Self::satisfied_count(specifications, candidate);
+                if satisfied_count >= *count {
+                    return None;
+                }
+                let unsatisfied = specifications
+                    .iter()
+                    .filter(|specification| !specification.is_satisfied_by(candidate))
+                    .map(|specification| {
+                        specification
+                            .reminder_unsatisfied_by(candidate)
+                            .unwrap_or_else(|| specification.clone())
+                    })
+                    .collect();
+                Some(Self::MinSatisfied(count - satisfied_count, unsatisfied))
+            }
+            // Too many children held, those are the ones to blame.
+            Self::MaxSatisfied(count, specifications) => {
+                if self.is_satisfied_by(candidate) {
+                    return None;
+                }
+                let satisfied = specifications
+                    .iter()
+                    .filter(|specification| specification.is_satisfied_by(candidate))
+                    .cloned()
+                    .collect();
+                Some(Self::MaxSatisfied(*count, satisfied))
+            }
             Self::Specification(f) => {
                 if f.is_satisfied_by(candidate) {
                     return None;
@@ -200,6 +544,260 @@ impl<T: std::fmt::Debug> SpecificationCompositions<T> {
             }
         }
     }
+
+    /// The positive counterpart of the reminder: the part of the composition that made the
+    /// `candidate` satisfy it, `None` if it doesn't. An `Or` is explained by its first
+    /// satisfied alternative, an `And` by the reasons of all of its children.
+    pub fn reasons_satisfied_by(&self, candidate: &T) -> Option<Self> {
+        if !self.is_satisfied_by(candidate) {
+            return None;
+        }
+        let reasons = |specifications: &[Self]| -> Vec<Self> {
+            specifications
+                .iter()
+                .filter_map(|specification| specification.reasons_satisfied_by(candidate))
+                .collect()
+        };
+        match self {
+            Self::And(specifications) => {
+                let mut satisfied = reasons(specifications);
+                if satisfied.len() == 1 {
+                    return Some(satisfied.remove(0));
+                }
+                Some(Self::And(satisfied))
+            }
+            Self::Or(specifications) => specifications
+                .iter()
+                .find_map(|specification| specification.reasons_satisfied_by(candidate)),
+            // The inverted specification failed, for the reasons its reminder tells.
+            Self::Invert(specification) => Some(Self::Invert(Box::new(
+                specification
+                    .reminder_unsatisfied_by(candidate)
+                    .unwrap_or_else(|| (**specification).clone()),
+            ))),
+            // An odd number of the children held, those are the reasons.
+            Self::Xor(specifications) => {
+                let mut satisfied = reasons(specifications);
+                if satisfied.len() == 1 {
+                    return Some(satisfied.remove(0));
+                }
+                Some(Self::Xor(satisfied))
+            }
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let satisfied = specifications
+                    .iter()
+                    .filter_map(|(weight, specification)| {
+                        specification
+                            .reasons_satisfied_by(candidate)
+                            .map(|reasons| (*weight, reasons))
+                    })
+                    .collect();
+                Some(Self::WeightedAtLeast(*threshold, satisfied))
+            }
+            Self::MinSatisfied(count, specifications) => {
+                Some(Self::MinSatisfied(*count, reasons(specifications)))
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                Some(Self::MaxSatisfied(*count, reasons(specifications)))
+            }
+            Self::True | Self::False | Self::Specification(..) => Some(self.clone()),
+        }
+    }
+
+    // The unsatisfied remainder of a failing candidate, `None` for a passing one. Where the
+    // reminder can't tell, like for a failing `Invert` whose inner specification holds,
+    // or for `False`, the failing node itself stands in for it.
+    pub(crate) fn failure_reminder(&self, candidate: &T) -> Option<Self> {
+        if self.is_satisfied_by(candidate) {
+            return None;
+        }
+        match self {
+            Self::And(specifications) => {
+                let mut unsatisfied: Vec<Self> = specifications
+                    .iter()
+                    .filter_map(|specification| specification.failure_reminder(candidate))
+                    .collect();
+                if unsatisfied.len() == 1 {
+                    return Some(unsatisfied.remove(0));
+                }
+                Some(Self::And(unsatisfied))
+            }
+            _ => Some(
+                self.reminder_unsatisfied_by(candidate)
+                    .unwrap_or_else(|| self.clone()),
+            ),
+        }
+    }
+
+    /// Pushes the leaves of the unsatisfied remainder into `buf`, without building the
+    /// remainder itself, so one buffer can be reused across many candidates.
+    /// The buffer is not cleared, the leaves are appended.
+    pub fn reminder_into(&self, candidate: &T, buf: &mut Vec<Arc<dyn Specification<T>>>) {
+        match self {
+            Self::Specification(f) => {
+                if !f.is_satisfied_by(candidate) {
+                    buf.push(f.clone());
+                }
+            }
+            Self::And(specifications) => {
+                for specification in specifications {
+                    if !specification.is_satisfied_by(candidate) {
+                        specification.reminder_into(candidate, buf);
+                    }
+                }
+            }
+            Self::Or(specifications) => {
+                if self.is_satisfied_by(candidate) {
+                    return;
+                }
+                for specification in specifications {
+                    specification.reminder_or_leaves_into(candidate, buf);
+                }
+            }
+            Self::Xor(specifications) => {
+                if self.is_satisfied_by(candidate) {
+                    return;
+                }
+                let satisfied: Vec<&Self> = specifications
+                    .iter()
+                    .filter(|specification| specification.is_satisfied_by(candidate))
+                    .collect();
+                if satisfied.is_empty() {
+                    for specification in specifications {
+                        specification.reminder_or_leaves_into(candidate, buf);
+                    }
+                } else {
+                    for specification in satisfied {
+                        specification.for_each_leaf(&mut |leaf| buf.push(leaf.clone()));
+                    }
+                }
+            }
+            Self::Invert(specification) => specification.reminder_into(candidate, buf),
+            Self::True | Self::False => {}
+            Self::WeightedAtLeast(..) | Self::MinSatisfied(..) => {
+                if self.is_satisfied_by(candidate) {
+                    return;
+                }
+                for specification in self.children() {
+                    if !specification.is_satisfied_by(candidate) {
+                        specification.reminder_or_leaves_into(candidate, buf);
+                    }
+                }
+            }
+            Self::MaxSatisfied(_, specifications) => {
+                if self.is_satisfied_by(candidate) {
+                    return;
+                }
+                for specification in specifications {
+                    if specification.is_satisfied_by(candidate) {
+                        specification.for_each_leaf(&mut |leaf| buf.push(leaf.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    // Like `reminder_unsatisfied_by(..).unwrap_or(self)`, falls back to all the leaves.
+    fn reminder_or_leaves_into(&self, candidate: &T, buf: &mut Vec<Arc<dyn Specification<T>>>) {
+        let len = buf.len();
+        self.reminder_into(candidate, buf);
+        if buf.len() == len {
+            self.for_each_leaf(&mut |leaf| buf.push(leaf.clone()));
+        }
+    }
+}
+
+// A leaf equals itself, and the leaves opting in with `eq_by_debug` equal those printing
+// the same `Debug`. Closures print alike whatever they do, so they can't be told apart.
+impl<T: std::fmt::Debug> PartialEq for SpecificationCompositions<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Specification(a), Self::Specification(b)) => {
+                Arc::ptr_eq(a, b)
+                    || (a.eq_by_debug()
+                        && b.eq_by_debug()
+                        && format!("{:?}", a) == format!("{:?}", b))
+            }
+            (Self::And(a), Self::And(b))
+            | (Self::Or(a), Self::Or(b))
+            | (Self::Xor(a), Self::Xor(b)) => a == b,
+            (Self::Invert(a), Self::Invert(b)) => a == b,
+            (Self::True, Self::True) | (Self::False, Self::False) => true,
+            // Weights compare bit for bit, like they are hashed, to keep `Eq` an equivalence.
+            (Self::WeightedAtLeast(a_threshold, a), Self::WeightedAtLeast(b_threshold, b)) => {
+                a_threshold.to_bits() == b_threshold.to_bits()
+                    && a.len() == b.len()
+                    && a.iter().zip(b).all(|((a_weight, a), (b_weight, b))| {
+                        a_weight.to_bits() == b_weight.to_bits() && a == b
+                    })
+            }
+            (Self::MinSatisfied(a_count, a), Self::MinSatisfied(b_count, b))
+            | (Self::MaxSatisfied(a_count, a), Self::MaxSatisfied(b_count, b)) => {
+                a_count == b_count && a == b
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> Eq for SpecificationCompositions<T> {}
+
+impl<T: std::fmt::Debug> Hash for SpecificationCompositions<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Specification(f) if f.eq_by_debug() => format!("{:?}", f).hash(state),
+            Self::Specification(f) => (Arc::as_ptr(f) as *const () as usize).hash(state),
+            Self::And(specifications) | Self::Or(specifications) | Self::Xor(specifications) => {
+                specifications.hash(state)
+            }
+            Self::Invert(specification) => specification.hash(state),
+            Self::True | Self::False => {}
+            Self::WeightedAtLeast(threshold, specifications) => {
+                threshold.to_bits().hash(state);
+                for (weight, specification) in specifications {
+                    weight.to_bits().hash(state);
+                    specification.hash(state);
+                }
+            }
+            Self::MinSatisfied(count, specifications)
+            | Self::MaxSatisfied(count, specifications) => {
+                count.hash(state);
+                specifications.hash(state);
+            }
+        }
+    }
+}
+
+// Leaves are formatted by their name instead of their own `Debug`, so the output only
+// depends on the structure of the tree and stays stable for snapshot tests.
+impl<T: std::fmt::Debug> std::fmt::Debug for SpecificationCompositions<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Specification(s) => f.debug_tuple("Specification").field(&s.name()).finish(),
+            Self::And(specifications) => f.debug_tuple("And").field(specifications).finish(),
+            Self::Or(specifications) => f.debug_tuple("Or").field(specifications).finish(),
+            Self::Xor(specifications) => f.debug_tuple("Xor").field(specifications).finish(),
+            Self::Invert(specification) => f.debug_tuple("Invert").field(specification).finish(),
+            Self::True => write!(f, "True"),
+            Self::False => write!(f, "False"),
+            Self::WeightedAtLeast(threshold, specifications) => f
+                .debug_tuple("WeightedAtLeast")
+                .field(threshold)
+                .field(specifications)
+                .finish(),
+            Self::MinSatisfied(count, specifications) => f
+                .debug_tuple("MinSatisfied")
+                .field(count)
+                .field(specifications)
+                .finish(),
+            Self::MaxSatisfied(count, specifications) => f
+                .debug_tuple("MaxSatisfied")
+                .field(count)
+                .field(specifications)
+                .finish(),
+        }
+    }
 }
 
 impl<T: std::fmt::Debug> Display for SpecificationCompositions<T> {
@@ -239,6 +837,36 @@ impl<T: std::fmt::Debug> Display for SpecificationCompositions<T> {
             }
             Self::True => write!(f, "true"),
             Self::False => write!(f, "false"),
+            Self::WeightedAtLeast(threshold, specifications) => {
+                write!(f, "at least {} of (", threshold)?;
+                for (i, (weight, specification)) in specifications.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} * {}", weight, specification)?;
+                }
+                write!(f, ")")
+            }
+            Self::MinSatisfied(count, specifications) => {
+                write!(f, "min {} of (", count)?;
+                for (i, specification) in specifications.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", specification)?;
+                }
+                write!(f, ")")
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                write!(f, "max {} of (", count)?;
+                for (i, specification) in specifications.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", specification)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -246,35 +874,7 @@ impl<T: std::fmt::Debug> Display for SpecificationCompositions<T> {
 #[cfg(test)]
 mod test {
     use super::*;
-
-    #[derive(Debug, Clone)]
-    struct GreaterThan {
-        value: i32,
-    }
-    impl Specification<i32> for GreaterThan {
-        fn is_satisfied_by(&self, candidate: &i32) -> bool {
-            candidate > &self.value
-        }
-    }
-
-    #[derive(Debug, Clone)]
-    struct LessThan {
-        value: i32,
-    }
-    impl Specification<i32> for LessThan {
-        fn is_satisfied_by(&self, candidate: &i32) -> bool {
-            candidate < &self.value
-        }
-    }
-
-    #[derive(Debug, Clone)]
-    struct Zero {}
-
-    impl Specification<i32> for Zero {
-        fn is_satisfied_by(&self, candidate: &i32) -> bool {
-            candidate == &0
-        }
-    }
+    use crate::fixtures::*;
 
     #[test]
     fn test_simple() {
@@ -287,6 +887,56 @@ mod test {
         assert!(!res);
     }
 
+    #[test]
+    fn test_constants_short_circuit() {
+        use SpecificationCompositions::{False, True};
+
+        assert_eq!(GreaterThan { value: 5 }.and(False), False);
+        assert_eq!(GreaterThan { value: 5 }.or(True), True);
+        assert_eq!(False.and(Zero {}), False);
+        assert_eq!(True.or(Zero {}), True);
+        assert_eq!(
+            True.and(GreaterThan { value: 5 }).and(True),
+            GreaterThan { value: 5 }.composite()
+        );
+        assert_eq!(
+            False.or(GreaterThan { value: 5 }).or(False),
+            GreaterThan { value: 5 }.composite()
+        );
+        assert_eq!(
+            GreaterThan { value: 5 }.and(Zero {}),
+            SpecificationCompositions::And(vec![
+                GreaterThan { value: 5 }.composite(),
+                Zero {}.composite()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        let specification = SpecificationCompositions::Or(vec![
+            GreaterThan { value: 5 }.and(LessThan { value: 10 }),
+            Zero {}.invert(),
+            SpecificationCompositions::True,
+        ]);
+
+        assert_eq!(
+            format!("{:?}", specification),
+            r#"Or([And([Specification("GreaterThan"), Specification("LessThan")]), Invert(Specification("Zero")), True])"#
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                SpecificationCompositions::MinSatisfied(1, vec![Zero {}.composite()])
+            ),
+            r#"MinSatisfied(1, [Specification("Zero")])"#
+        );
+        assert_eq!(
+            format!("{:#?}", Zero {}.invert()),
+            "Invert(\n    Specification(\n        \"Zero\",\n    ),\n)"
+        );
+    }
+
     #[test]
     fn test_and() {
         let greater_than_5 = GreaterThan { value: 5 };
@@ -344,6 +994,484 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_and_then_applies_factory() {
+        let specification = GreaterThan { value: 5 }
+            .or(Zero {})
+            .and_then(|built| match built {
+                SpecificationCompositions::Or(_) => LessThan { value: 10 }.composite(),
+                _ => SpecificationCompositions::True,
+            });
+
+        assert!(matches!(
+            &specification,
+            SpecificationCompositions::And(children)
+                if matches!(children[..], [SpecificationCompositions::Or(_), SpecificationCompositions::Specification(_)])
+        ));
+        assert!(specification.is_satisfied_by(&6));
+        assert!(specification.is_satisfied_by(&0));
+        assert!(!specification.is_satisfied_by(&33));
+    }
+
+    #[test]
+    fn test_and_then_skips_factory() {
+        let specification = GreaterThan { value: 5 }.and_then(|built| match built {
+            SpecificationCompositions::Or(_) => LessThan { value: 10 }.composite(),
+            _ => SpecificationCompositions::True,
+        });
+
+        assert!(matches!(
+            specification,
+            SpecificationCompositions::Specification(..)
+        ));
+        assert!(specification.is_satisfied_by(&33));
+    }
+
+    #[test]
+    fn test_composition_stays_structural() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 }.or(Zero {}));
+
+        assert!(matches!(
+            &specification,
+            SpecificationCompositions::And(children)
+                if matches!(children[1], SpecificationCompositions::Or(_))
+        ));
+
+        let specification = GreaterThan { value: 5 }.xor(LessThan { value: 10 }.and(Zero {}));
+        assert!(matches!(
+            &specification,
+            SpecificationCompositions::Xor(children)
+                if matches!(children[1], SpecificationCompositions::And(_))
+        ));
+
+        fn invert_any(
+            specification: impl Specification<i32> + 'static,
+        ) -> SpecificationCompositions<i32> {
+            specification.invert()
+        }
+        let specification = invert_any(LessThan { value: 10 }.or(Zero {}));
+        assert!(matches!(
+            &specification,
+            SpecificationCompositions::Invert(inverted)
+                if matches!(**inverted, SpecificationCompositions::Or(_))
+        ));
+
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .and(Zero {}.and(GreaterThan { value: -1 }));
+        assert!(matches!(
+            &specification,
+            SpecificationCompositions::And(children) if children.len() == 4
+        ));
+    }
+
+    #[test]
+    fn test_array_empty() {
+        let specification: [GreaterThan; 0] = [];
+
+        assert!(specification.is_satisfied_by(&0));
+    }
+
+    #[test]
+    fn test_array_single() {
+        let specification = [GreaterThan { value: 5 }];
+
+        assert!(specification.is_satisfied_by(&6));
+        assert!(!specification.is_satisfied_by(&5));
+    }
+
+    #[test]
+    fn test_array_several() {
+        let specification = [
+            GreaterThan { value: 5 }.composite(),
+            LessThan { value: 10 }.composite(),
+            Zero {}.invert(),
+        ];
+
+        assert!(specification.is_satisfied_by(&6));
+        assert!(!specification.is_satisfied_by(&3));
+        assert!(!specification.is_satisfied_by(&10));
+
+        let specification = [GreaterThan { value: -5 }.composite(), Zero {}.invert()];
+        assert!(!specification.is_satisfied_by(&0));
+    }
+
+    #[test]
+    fn test_tuple_of_two() {
+        let specification = (GreaterThan { value: 5 }, LessThan { value: 10 });
+
+        assert!(specification.is_satisfied_by(&6));
+        assert!(!specification.is_satisfied_by(&3));
+        assert!(!specification.is_satisfied_by(&33));
+    }
+
+    #[test]
+    fn test_tuple_of_three() {
+        let specification = (
+            GreaterThan { value: -5 },
+            LessThan { value: 10 },
+            Zero {}.invert(),
+        );
+
+        assert!(specification.is_satisfied_by(&6));
+        assert!(!specification.is_satisfied_by(&0));
+        assert!(!specification.is_satisfied_by(&-6));
+
+        let specification = specification.and(GreaterThan { value: 5 });
+        assert!(!specification.is_satisfied_by(&3));
+        assert!(specification.is_satisfied_by(&6));
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(GreaterThan { value: 5 }.name(), "GreaterThan");
+        assert_eq!(GreaterThan { value: 5 }.composite().name(), "GreaterThan");
+        assert_eq!(GreaterThan { value: 5 }.or(Zero {}).name(), "or");
+    }
+
+    #[test]
+    fn test_or_reminder_all_failed() {
+        let specification = GreaterThan { value: 5 }.or(Zero {});
+
+        let res = specification.reminder_unsatisfied_by(&3);
+
+        assert_eq!(
+            res.unwrap().to_string(),
+            "(GreaterThan { value: 5 } or Zero)"
+        );
+    }
+
+    #[test]
+    fn test_or_reminder_satisfied() {
+        let specification = GreaterThan { value: 5 }.or(Zero {});
+
+        assert!(specification.reminder_unsatisfied_by(&0).is_none());
+        assert!(specification.reminder_unsatisfied_by(&6).is_none());
+    }
+
+    #[test]
+    fn test_or_reasons_satisfied_by() {
+        let specification = GreaterThan { value: 5 }
+            .or(Zero {})
+            .or(LessThan { value: 2 });
+
+        assert_eq!(
+            specification.reasons_satisfied_by(&0).unwrap().to_string(),
+            "Zero"
+        );
+        assert_eq!(
+            specification.reasons_satisfied_by(&1).unwrap().to_string(),
+            "LessThan { value: 2 }"
+        );
+        assert!(specification.reasons_satisfied_by(&3).is_none());
+    }
+
+    #[test]
+    fn test_nested_reasons_satisfied_by() {
+        let specification = GreaterThan { value: 5 }
+            .or(Zero {}.and(LessThan { value: 2 }))
+            .and(LessThan { value: 10 }.or(Zero {}.invert()));
+
+        assert_eq!(
+            specification.reasons_satisfied_by(&0).unwrap().to_string(),
+            "((Zero and LessThan { value: 2 }) and LessThan { value: 10 })"
+        );
+        assert_eq!(
+            specification.reasons_satisfied_by(&12).unwrap().to_string(),
+            "(GreaterThan { value: 5 } and not Zero)"
+        );
+    }
+
+    #[test]
+    fn test_xor_parity() {
+        let specification = GreaterThan { value: 5 }
+            .xor(LessThan { value: 10 })
+            .xor(Zero {}.invert());
+
+        // All three hold.
+        assert!(specification.is_satisfied_by(&6));
+        // Exactly two hold.
+        assert!(!specification.is_satisfied_by(&3));
+        // Only one holds.
+        assert!(specification.is_satisfied_by(&0));
+    }
+
+    #[test]
+    fn test_xor_reminder_both_satisfied() {
+        let specification = GreaterThan { value: 5 }.xor(LessThan { value: 10 });
+
+        let res = specification.reminder_unsatisfied_by(&6);
+
+        assert!(matches!(
+            &res,
+            Some(SpecificationCompositions::Xor(held)) if held.len() == 2
+        ));
+        assert_eq!(
+            res.unwrap().to_string(),
+            "(GreaterThan { value: 5 } xor LessThan { value: 10 })"
+        );
+    }
+
+    #[test]
+    fn test_xor_reminder() {
+        let specification = GreaterThan { value: 5 }.xor(Zero {});
+
+        assert!(specification.reminder_unsatisfied_by(&6).is_none());
+        assert!(matches!(
+            specification.reminder_unsatisfied_by(&3),
+            Some(SpecificationCompositions::Xor(missing)) if missing.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_eq() {
+        let a = GreaterThan { value: 5 }.and(Zero {}.invert());
+        let b = GreaterThan { value: 5 }.and(Zero {}.invert());
+        let c = GreaterThan { value: 6 }.and(Zero {}.invert());
+        let d = Zero {}.invert().and(GreaterThan { value: 5 });
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, d);
+        assert_eq!(std::collections::HashSet::from([a.clone(), b, c]).len(), 2);
+    }
+
+    #[test]
+    fn test_eq_closure_leaves() {
+        use crate::comparison::{fields_compare, CmpOp};
+
+        let above_5 = fields_compare(|c: &i32| *c, |_: &i32| 5, CmpOp::Gt).composite();
+        let above_6 = fields_compare(|c: &i32| *c, |_: &i32| 6, CmpOp::Gt).composite();
+
+        assert_eq!(format!("{:?}", above_5), format!("{:?}", above_6));
+        assert_ne!(above_5, above_6);
+        assert_eq!(above_5, above_5.clone());
+        assert_eq!(
+            std::collections::HashSet::from([above_5.clone(), above_5, above_6]).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_eq_weights_bitwise() {
+        let weighted = |weight: f64| {
+            SpecificationCompositions::WeightedAtLeast(weight, vec![(weight, Zero {}.composite())])
+        };
+
+        assert_ne!(weighted(0.0), weighted(-0.0));
+        assert_eq!(weighted(f64::NAN), weighted(f64::NAN));
+        assert_eq!(weighted(1.5), weighted(1.5));
+    }
+
+    #[test]
+    fn test_find_satisfying() {
+        let between_5_and_10 = GreaterThan { value: 4 }.and(LessThan { value: 11 });
+
+        assert_eq!(between_5_and_10.find_satisfying(-100..100), Some(5));
+        assert_eq!(between_5_and_10.find_satisfying(7..100), Some(7));
+        assert_eq!(between_5_and_10.find_satisfying(11..100), None);
+    }
+
+    #[test]
+    fn test_find_satisfying_contradiction() {
+        let contradiction = GreaterThan { value: 10 }.and(LessThan { value: 5 });
+
+        assert_eq!(contradiction.find_satisfying(-100..100), None);
+    }
+
+    #[test]
+    fn test_satisfied_by_into() {
+        #[derive(Debug)]
+        struct NonEmpty;
+
+        impl Specification<String> for NonEmpty {
+            fn is_satisfied_by(&self, candidate: &String) -> bool {
+                !candidate.is_empty()
+            }
+        }
+
+        assert!(NonEmpty.satisfied_by_into("rust"));
+        assert!(!NonEmpty.satisfied_by_into(""));
+        assert!(GreaterThan { value: 5 }.satisfied_by_into(6u8));
+    }
+
+    // Neither `Clone` nor `Copy`, the `_ref` helpers must make do with references.
+    #[derive(Debug, PartialEq)]
+    struct Unclonable(i32);
+
+    #[derive(Debug)]
+    struct Positive;
+
+    impl Specification<Unclonable> for Positive {
+        fn is_satisfied_by(&self, candidate: &Unclonable) -> bool {
+            candidate.0 > 0
+        }
+    }
+
+    #[test]
+    fn test_ref_helpers() {
+        let items = vec![Unclonable(1), Unclonable(-2), Unclonable(3)];
+
+        let (satisfied, unsatisfied) = Positive.partition_ref(&items);
+        assert_eq!(satisfied, vec![&items[0], &items[2]]);
+        assert_eq!(unsatisfied, vec![&items[1]]);
+        assert!(std::ptr::eq(satisfied[0], &items[0]));
+
+        assert_eq!(
+            Positive.filter_ref(&items),
+            vec![&Unclonable(1), &Unclonable(3)]
+        );
+        assert_eq!(Positive.count_satisfied_ref(&items), 2);
+        assert_eq!(Positive.invert().count_satisfied_ref(&items), 1);
+        assert_eq!(Positive.count_satisfied_ref(&[]), 0);
+    }
+
+    #[test]
+    fn test_satisfied_by_all() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 });
+        let owned = vec![6, 7, 9];
+        let borrowed: Vec<&i32> = owned.iter().collect();
+
+        assert!(specification.satisfied_by_all(borrowed));
+        assert!(specification.satisfied_by_all(owned));
+        assert!(!specification.satisfied_by_all(vec![6, 10]));
+        assert!(!specification.satisfied_by_all([&6, &3]));
+        assert!(specification.satisfied_by_all(Vec::<i32>::new()));
+    }
+
+    #[test]
+    fn test_reminder_into() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .or(Zero {})
+            .and(
+                GreaterThan { value: 20 }
+                    .xor(LessThan { value: 30 })
+                    .or(Zero {}),
+            );
+        let mut buf = Vec::new();
+
+        for candidate in -10..40 {
+            buf.clear();
+            specification.reminder_into(&candidate, &mut buf);
+
+            let mut expected = Vec::new();
+            if let Some(reminder) = specification.reminder_unsatisfied_by(&candidate) {
+                reminder.for_each_leaf(&mut |leaf| expected.push(leaf.clone()));
+            }
+            assert_eq!(buf.len(), expected.len(), "candidate {}", candidate);
+            for (leaf, expected) in buf.iter().zip(&expected) {
+                assert!(Arc::ptr_eq(leaf, expected), "candidate {}", candidate);
+            }
+        }
+    }
+
+    fn weighted() -> SpecificationCompositions<i32> {
+        SpecificationCompositions::WeightedAtLeast(
+            3.0,
+            vec![
+                (2.0, GreaterThan { value: 5 }.composite()),
+                (1.5, LessThan { value: 10 }.composite()),
+                (1.0, Zero {}.invert()),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_weighted_at_least() {
+        let specification = weighted();
+
+        // 2.0 + 1.5 + 1.0
+        assert!(specification.is_satisfied_by(&6));
+        // 2.0 + 1.0
+        assert!(specification.is_satisfied_by(&20));
+        // 1.5 + 1.0
+        assert!(!specification.is_satisfied_by(&3));
+        // 1.5
+        assert!(!specification.is_satisfied_by(&0));
+        assert_eq!(
+            specification.to_string(),
+            "at least 3 of (2 * GreaterThan { value: 5 }, 1.5 * LessThan { value: 10 }, 1 * not Zero)"
+        );
+    }
+
+    #[test]
+    fn test_weighted_at_least_reminder() {
+        let specification = weighted();
+
+        assert!(specification.reminder_unsatisfied_by(&20).is_none());
+        let reminder = specification.reminder_unsatisfied_by(&0).unwrap();
+        assert_eq!(
+            reminder.to_string(),
+            "at least 1.5 of (2 * GreaterThan { value: 5 }, 1 * not Zero)"
+        );
+    }
+
+    fn counted() -> Vec<SpecificationCompositions<i32>> {
+        vec![
+            GreaterThan { value: 5 }.composite(),
+            LessThan { value: 10 }.composite(),
+            Zero {}.invert(),
+        ]
+    }
+
+    #[test]
+    fn test_min_satisfied() {
+        let specification = SpecificationCompositions::MinSatisfied(2, counted());
+        let at_least = SpecificationCompositions::WeightedAtLeast(
+            2.0,
+            counted().into_iter().map(|child| (1.0, child)).collect(),
+        );
+
+        assert_eq!(
+            specification.to_string(),
+            "min 2 of (GreaterThan { value: 5 }, LessThan { value: 10 }, not Zero)"
+        );
+        for candidate in -5..15 {
+            assert_eq!(
+                specification.is_satisfied_by(&candidate),
+                at_least.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_satisfied() {
+        let specification = SpecificationCompositions::MaxSatisfied(2, counted());
+        let at_most = SpecificationCompositions::WeightedAtLeast(
+            3.0,
+            counted().into_iter().map(|child| (1.0, child)).collect(),
+        )
+        .invert();
+
+        assert_eq!(
+            specification.to_string(),
+            "max 2 of (GreaterThan { value: 5 }, LessThan { value: 10 }, not Zero)"
+        );
+        for candidate in -5..15 {
+            assert_eq!(
+                specification.is_satisfied_by(&candidate),
+                at_most.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_max_satisfied_reminder() {
+        let min = SpecificationCompositions::MinSatisfied(3, counted());
+        let max = SpecificationCompositions::MaxSatisfied(2, counted());
+
+        assert_eq!(
+            min.reminder_unsatisfied_by(&0).unwrap().to_string(),
+            "min 2 of (GreaterThan { value: 5 }, not Zero)"
+        );
+        assert!(max.reminder_unsatisfied_by(&0).is_none());
+        assert_eq!(
+            max.reminder_unsatisfied_by(&7).unwrap().to_string(),
+            "max 2 of (GreaterThan { value: 5 }, LessThan { value: 10 }, not Zero)"
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_similarity() {
@@ -2,7 +2,7 @@
 // You want to filter out the candidates that don't meet your criteria.
 // Disclaimer: This is a fictional example, demonstrating the use of the specification pattern.
 
-use specification::Specification;
+use specification::{Specification, SpecificationCompositions};
 
 #[derive(Debug, Clone)]
 struct JobCandidate {
@@ -77,7 +77,7 @@ const fn yes_or_no(b: bool) -> &'static str {
     }
 }
 
-fn main() {
+fn good_for_interview() -> SpecificationCompositions<JobCandidate> {
     // Let's define our criteria, as our Boss said:
     // "We need someone with at least 10 years of experience,
     // who has contributed to at least 5 open source projects,
@@ -115,49 +115,56 @@ fn main() {
     let satisfies_experience_requirement =
         ten_years_of_experience.or(five_years_of_experience.and(have_science_degree));
 
-    let good_for_interview = satisfies_minimum_requirement
-        .and(satisfies_salary_requirement)
-        .and(satisfies_experience_requirement);
-
-    // ^^^ I think that's pretty readable given the complexity of the requirements.
+    // I think that's pretty readable given the complexity of the requirements.
     // Ok, that invert is a bit ugly, but wouln't take long to have a nice `not` function,
     // and have something like this: `let desires_non_rust_programmer_salary = not(worked_with_rust).and(desire_no_more_than_90k);`
+    satisfies_minimum_requirement
+        .and(satisfies_salary_requirement)
+        .and(satisfies_experience_requirement)
+}
 
-    let candidate_a: JobCandidate = {
-        let languages_worked_with = vec![
-            "Rust".to_string(),
-            "C++".to_string(),
-            "Python".to_string(),
-            "Go".to_string(),
-        ];
+fn candidate_a() -> JobCandidate {
+    let languages_worked_with = vec![
+        "Rust".to_string(),
+        "C++".to_string(),
+        "Python".to_string(),
+        "Go".to_string(),
+    ];
 
-        JobCandidate {
-            name: "John".to_string(),
-            years_of_experience: 5.0,
-            github_contributions: 10,
-            languages_worked_with,
-            desired_salary: 100_000,
-            science_degree: true,
-        }
-    };
+    JobCandidate {
+        name: "John".to_string(),
+        years_of_experience: 5.0,
+        github_contributions: 10,
+        languages_worked_with,
+        desired_salary: 100_000,
+        science_degree: true,
+    }
+}
+
+fn candidate_b() -> JobCandidate {
+    let languages_worked_with = vec!["C++".to_string(), "Python".to_string(), "Go".to_string()];
+
+    JobCandidate {
+        name: "Mike".to_string(),
+        years_of_experience: 5.0,
+        github_contributions: 10,
+        languages_worked_with,
+        desired_salary: 100_000,
+        science_degree: true,
+    }
+}
+
+fn main() {
+    let good_for_interview = good_for_interview();
+
+    let candidate_a = candidate_a();
     println!(
         "Candidate A {}, is good for interview: {}",
         &candidate_a.name,
         yes_or_no(good_for_interview.is_satisfied_by(&candidate_a))
     );
 
-    let candidate_b: JobCandidate = {
-        let languages_worked_with = vec!["C++".to_string(), "Python".to_string(), "Go".to_string()];
-
-        JobCandidate {
-            name: "Mike".to_string(),
-            years_of_experience: 5.0,
-            github_contributions: 10,
-            languages_worked_with,
-            desired_salary: 100_000,
-            science_degree: true,
-        }
-    };
+    let candidate_b = candidate_b();
     println!(
         "Candidate B {}, is good for interview: {}",
         &candidate_b.name,
@@ -169,3 +176,262 @@ fn main() {
     );
     // I admit this isn't necessary the best output, but it is a good example.
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use specification::comparison::{fields_compare, CmpOp};
+    use specification::record::RecordedBuilder;
+    use specification::tiered::{TierResult, TieredSpecification};
+
+    fn candidate(
+        years_of_experience: f64,
+        github_contributions: i64,
+        languages: &[&str],
+        desired_salary: i64,
+    ) -> JobCandidate {
+        JobCandidate {
+            name: "Test".to_string(),
+            years_of_experience,
+            github_contributions,
+            languages_worked_with: languages.iter().map(|l| l.to_string()).collect(),
+            desired_salary,
+            science_degree: false,
+        }
+    }
+
+    #[test]
+    fn test_failure_histogram() {
+        let candidates = vec![
+            candidate_a(),
+            candidate_b(),
+            candidate(12.0, 2, &["Python"], 80_000),
+            candidate(3.0, 1, &["Python"], 80_000),
+        ];
+
+        let histogram = good_for_interview().failure_histogram(&candidates);
+
+        assert_eq!(histogram.len(), 5);
+        assert_eq!(histogram["MinimumGithubContributions"], 2);
+        assert_eq!(histogram["MinimumYearsOfExperience"], 1);
+        assert_eq!(histogram["HasScienceDegree"], 1);
+        assert_eq!(histogram["WorkedWithLanguage"], 1);
+        assert_eq!(histogram["MaxDesiredSalary"], 1);
+    }
+
+    #[test]
+    fn test_filtered_with_reasons() {
+        let candidates = vec![candidate_a(), candidate_b()];
+
+        let results = good_for_interview().filtered_with_reasons(&candidates);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.name, "John");
+        assert!(results[0].1.is_none());
+        assert_eq!(results[1].0.name, "Mike");
+        assert!(results[1]
+            .1
+            .as_ref()
+            .is_some_and(|reminder| reminder.to_string().contains("MaxDesiredSalary")));
+    }
+
+    #[test]
+    fn test_satisfied_summary() {
+        let good_for_interview = good_for_interview();
+
+        assert_eq!(good_for_interview.satisfied_summary(&candidate_a()), "PASS");
+        assert_eq!(
+            good_for_interview.satisfied_summary(&candidate_b()),
+            "FAIL: WorkedWithLanguage, MaxDesiredSalary"
+        );
+        assert_eq!(
+            good_for_interview.satisfied_summary(&candidate(3.0, 1, &["Python"], 200_000)),
+            "FAIL: MinimumGithubContributions, WorkedWithLanguage, MaxDesiredSalary, MinimumYearsOfExperience, HasScienceDegree"
+        );
+    }
+
+    #[test]
+    fn test_concise_reason() {
+        let good_for_interview = good_for_interview();
+        let candidate = candidate(3.0, 1, &["Python"], 200_000);
+
+        assert_eq!(
+            good_for_interview.concise_reason(&candidate, 2),
+            vec!["MinimumGithubContributions", "WorkedWithLanguage"]
+        );
+        assert!(good_for_interview
+            .concise_reason(&candidate_a(), 2)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_labeled_requirements() {
+        let SpecificationCompositions::And(mut requirements) = good_for_interview() else {
+            panic!("expected an And");
+        };
+        let experience_requirement = requirements.pop().unwrap();
+        let salary_requirement = requirements.pop().unwrap();
+        let good_for_interview = SpecificationCompositions::And(requirements)
+            .labeled_and("salary requirement", salary_requirement)
+            .labeled_and("experience requirement", experience_requirement);
+
+        assert!(good_for_interview.is_satisfied_by(&candidate_a()));
+        assert_eq!(
+            good_for_interview.satisfied_summary(&candidate_b()),
+            "FAIL: salary requirement"
+        );
+        assert_eq!(
+            good_for_interview.satisfied_summary(&candidate(3.0, 6, &["Python"], 80_000)),
+            "FAIL: experience requirement"
+        );
+    }
+
+    #[test]
+    fn test_tiered() {
+        let worked_with = |language: &str| WorkedWithLanguage {
+            language: language.to_string(),
+        };
+        let tiered = TieredSpecification::new(
+            good_for_interview(),
+            worked_with("Rust")
+                .and(worked_with("Go"))
+                .and(MinimumYearsOfExperience { min_years: 10.0 }),
+        );
+
+        assert_eq!(
+            tiered.evaluate(&candidate_a()),
+            TierResult {
+                required_met: true,
+                preferred_satisfied: 2,
+                preferred_total: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_recorded_builder() {
+        let worked_with = |language: &str| {
+            RecordedBuilder::leaf(WorkedWithLanguage {
+                language: language.to_string(),
+            })
+        };
+        let years = |min_years| RecordedBuilder::leaf(MinimumYearsOfExperience { min_years });
+        let salary = |max_salary| RecordedBuilder::leaf(MaxDesiredSalary { max_salary });
+        let worked_with_rust = worked_with("Rust");
+
+        let satisfies_minimum_requirement = RecordedBuilder::leaf(MinimumGithubContributions {
+            min_contributions: 5,
+        })
+        .and(worked_with("C++").or(worked_with("Python")));
+        let satisfies_salary_requirement = worked_with_rust
+            .clone()
+            .and(salary(130_000))
+            .or(worked_with_rust.invert().and(salary(90_000)));
+        let satisfies_experience_requirement =
+            years(10.0).or(years(5.0).and(RecordedBuilder::leaf(HasScienceDegree {})));
+        let builder = satisfies_minimum_requirement
+            .and(satisfies_salary_requirement)
+            .and(satisfies_experience_requirement);
+
+        assert_eq!(
+            builder.script(),
+            "leaf MinimumGithubContributions
+leaf WorkedWithLanguage
+leaf WorkedWithLanguage
+or
+and
+leaf WorkedWithLanguage
+leaf MaxDesiredSalary
+and
+leaf WorkedWithLanguage
+not
+leaf MaxDesiredSalary
+and
+or
+and
+leaf MinimumYearsOfExperience
+leaf MinimumYearsOfExperience
+leaf HasScienceDegree
+and
+or
+and"
+        );
+        assert_eq!(
+            builder.build().to_string(),
+            good_for_interview().to_string()
+        );
+    }
+
+    #[test]
+    fn test_dataset_stats() {
+        let candidates = vec![
+            candidate_a(),
+            candidate_b(),
+            candidate(12.0, 6, &["Python"], 80_000),
+            candidate(3.0, 1, &["Python"], 80_000),
+        ];
+
+        let stats = good_for_interview().dataset_stats(&candidates);
+
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.satisfied, 2);
+        assert_eq!(stats.satisfaction_rate, 0.5);
+        assert_eq!(stats.failure_histogram.len(), 5);
+        assert_eq!(stats.failure_histogram["MaxDesiredSalary"], 1);
+        assert_eq!(stats.failure_histogram["MinimumGithubContributions"], 1);
+        assert_eq!(stats.failure_histogram["MinimumYearsOfExperience"], 1);
+    }
+
+    #[test]
+    fn test_salary_branch() {
+        let salary_requirement = match good_for_interview() {
+            SpecificationCompositions::And(mut requirements) => requirements.remove(2),
+            _ => panic!("expected an And"),
+        };
+
+        assert_eq!(
+            salary_requirement.first_matching_branch(&candidate_a()),
+            Some(vec![0])
+        );
+        assert_eq!(
+            salary_requirement.first_matching_branch(&candidate_b()),
+            None
+        );
+        assert_eq!(
+            salary_requirement.first_matching_branch(&candidate(12.0, 6, &["Python"], 80_000)),
+            Some(vec![1])
+        );
+    }
+
+    #[test]
+    fn test_fields_compare() {
+        let more_contributions_than_years = fields_compare(
+            |candidate: &JobCandidate| candidate.github_contributions as f64,
+            |candidate: &JobCandidate| candidate.years_of_experience,
+            CmpOp::Gt,
+        );
+
+        assert!(more_contributions_than_years.is_satisfied_by(&candidate_a()));
+        assert!(!more_contributions_than_years.is_satisfied_by(&candidate(12.0, 6, &[], 80_000)));
+        assert!(!more_contributions_than_years.is_satisfied_by(&candidate(6.0, 6, &[], 80_000)));
+        assert!(good_for_interview()
+            .and(more_contributions_than_years)
+            .is_satisfied_by(&candidate_a()));
+    }
+
+    #[test]
+    fn test_leaf_names() {
+        let names: Vec<String> = good_for_interview().leaf_names().into_iter().collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "HasScienceDegree",
+                "MaxDesiredSalary",
+                "MinimumGithubContributions",
+                "MinimumYearsOfExperience",
+                "WorkedWithLanguage",
+            ]
+        );
+    }
+}
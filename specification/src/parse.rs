@@ -0,0 +1,332 @@
+// An infix text representation of rules, such as `GreaterThan5 and not (Zero or LessThan10)`.
+//
+// Operators bind, from the loosest to the tightest: `or` (`|`, `||`), `xor` (`^`),
+// `and` (`&`, `&&`) and the prefix `not` (`!`). The binary operators are left associative.
+
+use std::fmt::{Display, Formatter};
+
+use crate::registry::Registry;
+use crate::SpecificationCompositions;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownLeaf(String),
+    /// `position` is the byte offset of the offending token in the parsed text.
+    Syntax {
+        position: usize,
+        message: String,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownLeaf(tag) => write!(f, "unknown leaf `{}`", tag),
+            Self::Syntax { position, message } => {
+                write!(f, "at position {}: {}", position, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Parses an infix rule, looking up the leaves in the `registry` by their tag.
+    /// `true` and `false` are the constants, parentheses override the precedence.
+    pub fn parse(s: &str, registry: &Registry<T>) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            end: s.len(),
+            registry,
+        };
+        let specification = parser.expression(0)?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(specification),
+            Some(token) => Err(parser.error(token.position, "unexpected content after the rule")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    And,
+    Or,
+    Xor,
+}
+
+impl Operator {
+    // Left and right binding powers, the left one being lower makes the operator left associative.
+    fn binding_power(self) -> (u8, u8) {
+        match self {
+            Self::Or => (1, 2),
+            Self::Xor => (3, 4),
+            Self::And => (5, 6),
+        }
+    }
+}
+
+const NOT_BINDING_POWER: u8 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Content<'a> {
+    Binary(Operator),
+    Not,
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+    position: usize,
+    content: Content<'a>,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    const SYMBOLS: [(&str, Content<'static>); 8] = [
+        ("&&", Content::Binary(Operator::And)),
+        ("||", Content::Binary(Operator::Or)),
+        ("&", Content::Binary(Operator::And)),
+        ("|", Content::Binary(Operator::Or)),
+        ("^", Content::Binary(Operator::Xor)),
+        ("!", Content::Not),
+        ("(", Content::Open),
+        (")", Content::Close),
+    ];
+
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    'tokens: while let Some(c) = s[position..].chars().next() {
+        if c.is_whitespace() {
+            position += c.len_utf8();
+            continue;
+        }
+        for (symbol, content) in SYMBOLS {
+            if s[position..].starts_with(symbol) {
+                tokens.push(Token { position, content });
+                position += symbol.len();
+                continue 'tokens;
+            }
+        }
+        let length = s[position..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(s.len() - position);
+        if length == 0 {
+            return Err(ParseError::Syntax {
+                position,
+                message: format!("unexpected `{}`", c),
+            });
+        }
+        let content = match &s[position..position + length] {
+            "and" => Content::Binary(Operator::And),
+            "or" => Content::Binary(Operator::Or),
+            "xor" => Content::Binary(Operator::Xor),
+            "not" => Content::Not,
+            word => Content::Word(word),
+        };
+        tokens.push(Token { position, content });
+        position += length;
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a, 'r, T: std::fmt::Debug> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    end: usize,
+    registry: &'r Registry<T>,
+}
+
+impl<T: std::fmt::Debug> Parser<'_, '_, T> {
+    fn expression(
+        &mut self,
+        min_binding_power: u8,
+    ) -> Result<SpecificationCompositions<T>, ParseError> {
+        let mut specification = self.operand()?;
+        while let Some(Token {
+            content: Content::Binary(operator),
+            ..
+        }) = self.tokens.get(self.position)
+        {
+            let operator = *operator;
+            let (left, right) = operator.binding_power();
+            if left < min_binding_power {
+                break;
+            }
+            self.position += 1;
+            let other = self.expression(right)?;
+            specification = combine(operator, specification, other);
+        }
+        Ok(specification)
+    }
+
+    fn operand(&mut self) -> Result<SpecificationCompositions<T>, ParseError> {
+        let Some(token) = self.tokens.get(self.position) else {
+            return Err(self.error(self.end, "expected a rule"));
+        };
+        let (position, content) = (token.position, token.content);
+        self.position += 1;
+        match content {
+            Content::Not => Ok(SpecificationCompositions::Invert(Box::new(
+                self.expression(NOT_BINDING_POWER)?,
+            ))),
+            Content::Open => {
+                let specification = self.expression(0)?;
+                match self.tokens.get(self.position) {
+                    Some(Token {
+                        content: Content::Close,
+                        ..
+                    }) => {
+                        self.position += 1;
+                        Ok(specification)
+                    }
+                    Some(token) => Err(self.error(token.position, "expected `)`")),
+                    None => Err(self.error(self.end, "expected `)`")),
+                }
+            }
+            Content::Word("true") => Ok(SpecificationCompositions::True),
+            Content::Word("false") => Ok(SpecificationCompositions::False),
+            Content::Word(tag) => self
+                .registry
+                .get(tag)
+                .ok_or_else(|| ParseError::UnknownLeaf(tag.to_string())),
+            Content::Binary(..) | Content::Close => Err(self.error(position, "expected a rule")),
+        }
+    }
+
+    fn error(&self, position: usize, message: &str) -> ParseError {
+        ParseError::Syntax {
+            position,
+            message: message.to_string(),
+        }
+    }
+}
+
+// Chains of the same operator end up as the children of a single node.
+fn combine<T: std::fmt::Debug>(
+    operator: Operator,
+    specification: SpecificationCompositions<T>,
+    other: SpecificationCompositions<T>,
+) -> SpecificationCompositions<T> {
+    match (operator, specification) {
+        (Operator::And, SpecificationCompositions::And(mut specifications))
+        | (Operator::Or, SpecificationCompositions::Or(mut specifications))
+        | (Operator::Xor, SpecificationCompositions::Xor(mut specifications)) => {
+            specifications.push(other);
+            rebuild(operator, specifications)
+        }
+        (operator, specification) => rebuild(operator, vec![specification, other]),
+    }
+}
+
+fn rebuild<T: std::fmt::Debug>(
+    operator: Operator,
+    specifications: Vec<SpecificationCompositions<T>>,
+) -> SpecificationCompositions<T> {
+    match operator {
+        Operator::And => SpecificationCompositions::And(specifications),
+        Operator::Or => SpecificationCompositions::Or(specifications),
+        Operator::Xor => SpecificationCompositions::Xor(specifications),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+    use crate::Specification;
+
+    fn registry() -> Registry<i32> {
+        Registry::new()
+            .register(greater_than_5())
+            .register(less_than_10())
+            .register(Zero {})
+    }
+
+    fn parse(s: &str) -> Result<SpecificationCompositions<i32>, ParseError> {
+        SpecificationCompositions::parse(s, &registry())
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let parsed = parse("GreaterThan5 or LessThan10 and not Zero").unwrap();
+
+        assert_eq!(
+            parsed,
+            greater_than_5().or(less_than_10().and(Zero {}.invert()))
+        );
+    }
+
+    #[test]
+    fn test_parse_xor_between_or_and_and() {
+        let parsed = parse("Zero or GreaterThan5 xor LessThan10 and Zero").unwrap();
+
+        assert_eq!(
+            parsed,
+            Zero {}.or(greater_than_5().xor(less_than_10().and(Zero {})))
+        );
+    }
+
+    #[test]
+    fn test_parse_symbols() {
+        let keywords = parse("not Zero and (GreaterThan5 or LessThan10) xor true").unwrap();
+        let symbols = parse("!Zero && (GreaterThan5 | LessThan10) ^ true").unwrap();
+
+        assert_eq!(symbols, keywords);
+        assert_eq!(
+            symbols,
+            Zero {}
+                .invert()
+                .and(greater_than_5().or(less_than_10()))
+                .xor(SpecificationCompositions::True)
+        );
+    }
+
+    #[test]
+    fn test_parse_left_associative_chain() {
+        let parsed = parse("Zero & GreaterThan5 & LessThan10").unwrap();
+
+        assert_eq!(parsed.children().len(), 3);
+        assert_eq!(parsed, Zero {}.and(greater_than_5()).and(less_than_10()));
+    }
+
+    #[test]
+    fn test_parse_not_binds_tightest() {
+        let parsed = parse("not not Zero or false").unwrap();
+
+        assert_eq!(
+            parsed,
+            SpecificationCompositions::Or(vec![
+                Zero {}.invert().invert(),
+                SpecificationCompositions::False,
+            ])
+        );
+        assert!(parsed.is_satisfied_by(&0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("Zero or Unknown").unwrap_err(),
+            ParseError::UnknownLeaf("Unknown".to_string())
+        );
+        assert!(matches!(
+            parse("Zero or"),
+            Err(ParseError::Syntax { position: 7, .. })
+        ));
+        assert!(matches!(
+            parse("(Zero or GreaterThan5"),
+            Err(ParseError::Syntax { position: 21, .. })
+        ));
+        assert!(matches!(
+            parse("Zero $ GreaterThan5"),
+            Err(ParseError::Syntax { position: 5, .. })
+        ));
+        assert!(matches!(
+            parse("Zero GreaterThan5"),
+            Err(ParseError::Syntax { position: 5, .. })
+        ));
+    }
+}
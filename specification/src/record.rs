@@ -0,0 +1,204 @@
+// Building a composition while recording how it was built, to audit or replay it.
+
+use crate::parse::ParseError;
+use crate::registry::Registry;
+use crate::{Specification, SpecificationCompositions};
+
+/// Builds a composition like the combinator methods do, logging every step.
+///
+/// The log is a postfix script, one operation per line: `leaf <name>` pushes a leaf,
+/// `not` replaces the top of the stack with its negation, and `and`, `or` and `xor`
+/// combine the two topmost entries. [`RecordedBuilder::replay`] runs it again.
+#[derive(Debug)]
+pub struct RecordedBuilder<T: std::fmt::Debug> {
+    specification: SpecificationCompositions<T>,
+    operations: Vec<String>,
+}
+
+// Cloning only clones the `Arc`s of the leaves, so it doesn't need `T: Clone` as derive would.
+impl<T: std::fmt::Debug> Clone for RecordedBuilder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            specification: self.specification.clone(),
+            operations: self.operations.clone(),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug + 'static> RecordedBuilder<T> {
+    pub fn leaf(specification: impl Specification<T> + 'static) -> Self {
+        Self {
+            operations: vec![format!("leaf {}", specification.name())],
+            specification: specification.composite(),
+        }
+    }
+
+    pub fn and(self, other: Self) -> Self {
+        self.combine(other, "and", SpecificationCompositions::and)
+    }
+
+    pub fn or(self, other: Self) -> Self {
+        self.combine(other, "or", SpecificationCompositions::or)
+    }
+
+    pub fn xor(self, other: Self) -> Self {
+        self.combine(other, "xor", SpecificationCompositions::xor)
+    }
+
+    pub fn invert(mut self) -> Self {
+        self.operations.push("not".to_string());
+        Self {
+            specification: self.specification.invert(),
+            operations: self.operations,
+        }
+    }
+
+    fn combine(
+        mut self,
+        other: Self,
+        operation: &str,
+        combinator: impl FnOnce(
+            SpecificationCompositions<T>,
+            SpecificationCompositions<T>,
+        ) -> SpecificationCompositions<T>,
+    ) -> Self {
+        self.operations.extend(other.operations);
+        self.operations.push(operation.to_string());
+        Self {
+            specification: combinator(self.specification, other.specification),
+            operations: self.operations,
+        }
+    }
+
+    /// The operations so far, in the order they were applied.
+    pub fn operations(&self) -> &[String] {
+        &self.operations
+    }
+
+    /// The operations as a script for [`RecordedBuilder::replay`], one per line.
+    pub fn script(&self) -> String {
+        self.operations.join("\n")
+    }
+
+    pub fn build(self) -> SpecificationCompositions<T> {
+        self.specification
+    }
+
+    /// Rebuilds the composition a [`RecordedBuilder::script`] describes, looking up
+    /// the leaves in the `registry` by their name. Error positions are byte offsets of lines.
+    pub fn replay(
+        script: &str,
+        registry: &Registry<T>,
+    ) -> Result<SpecificationCompositions<T>, ParseError> {
+        let mut stack: Vec<Self> = Vec::new();
+        let mut position = 0;
+        for line in script.split('\n') {
+            let error = |message: &str| ParseError::Syntax {
+                position,
+                message: message.to_string(),
+            };
+            let operation = line.trim();
+            let builder = match operation.split_once(' ') {
+                Some(("leaf", name)) => {
+                    let leaf = registry
+                        .get(name)
+                        .ok_or_else(|| ParseError::UnknownLeaf(name.to_string()))?;
+                    Self::leaf(leaf)
+                }
+                _ if operation == "not" => stack
+                    .pop()
+                    .ok_or_else(|| error("nothing to negate"))?
+                    .invert(),
+                _ if matches!(operation, "and" | "or" | "xor") => {
+                    let (Some(other), Some(builder)) = (stack.pop(), stack.pop()) else {
+                        return Err(error("expected two operands"));
+                    };
+                    match operation {
+                        "and" => builder.and(other),
+                        "or" => builder.or(other),
+                        _ => builder.xor(other),
+                    }
+                }
+                _ => return Err(error("unknown operation")),
+            };
+            stack.push(builder);
+            position += line.len() + 1;
+        }
+        match (stack.pop(), stack.is_empty()) {
+            (Some(builder), true) => Ok(builder.build()),
+            _ => Err(ParseError::Syntax {
+                position: script.len(),
+                message: "expected a single rule".to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    fn registry() -> Registry<i32> {
+        Registry::new()
+            .register(greater_than_5())
+            .register(less_than_10())
+            .register(Zero {})
+    }
+
+    #[test]
+    fn test_operations() {
+        let builder = RecordedBuilder::leaf(greater_than_5())
+            .and(RecordedBuilder::leaf(less_than_10()))
+            .or(RecordedBuilder::leaf(Zero {}).invert());
+
+        assert_eq!(
+            builder.operations(),
+            [
+                "leaf GreaterThan5",
+                "leaf LessThan10",
+                "and",
+                "leaf Zero",
+                "not",
+                "or"
+            ]
+        );
+        assert_eq!(
+            builder.build(),
+            greater_than_5().and(less_than_10()).or(Zero {}.invert())
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let builder = RecordedBuilder::leaf(greater_than_5())
+            .xor(RecordedBuilder::leaf(Zero {}))
+            .and(RecordedBuilder::leaf(less_than_10()).invert());
+
+        let replayed = RecordedBuilder::replay(&builder.script(), &registry()).unwrap();
+
+        assert_eq!(replayed, builder.build());
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        assert_eq!(
+            RecordedBuilder::replay("leaf GreaterThan5\nleaf Missing\nand", &registry()),
+            Err(ParseError::UnknownLeaf("Missing".to_string()))
+        );
+        assert_eq!(
+            RecordedBuilder::replay("leaf Zero\nand", &registry()),
+            Err(ParseError::Syntax {
+                position: 10,
+                message: "expected two operands".to_string()
+            })
+        );
+        assert_eq!(
+            RecordedBuilder::replay("leaf Zero\nleaf Zero", &registry()),
+            Err(ParseError::Syntax {
+                position: 19,
+                message: "expected a single rule".to_string()
+            })
+        );
+    }
+}
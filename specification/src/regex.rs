@@ -0,0 +1,661 @@
+// A small hand written regular expression engine, matching strings against patterns
+// without depending on the regex crate. It backtracks, so it suits the short formatted
+// strings specifications check, like codes and dates, not searching large texts.
+//
+// Supported: literals, `.`, classes like `[a-z_]` and `[^0-9]`, `\d` `\w` `\s` and their
+// negations, `^` `$`, groups `(...)`, `(?:...)`, named `(?P<name>...)` or `(?<name>...)`,
+// alternation `|`, and the quantifiers `*` `+` `?` `{n}` `{n,}` `{n,m}`, lazy with a `?`.
+
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::Specification;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexError {
+    /// The pattern is malformed, the position counts characters from 0.
+    Syntax { position: usize, message: String },
+    /// The capture group, by name or index, isn't in the pattern.
+    UnknownGroup(String),
+}
+
+impl Display for RegexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax { position, message } => write!(f, "at {}: {}", position, message),
+            Self::UnknownGroup(group) => write!(f, "unknown capture group `{}`", group),
+        }
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+/// A compiled pattern. As a specification it is satisfied by the strings it matches
+/// somewhere, anchor it with `^` and `$` to match the whole string.
+#[derive(Debug)]
+pub struct Regex {
+    pattern: String,
+    root: Node,
+    // Group 0 is the whole match.
+    groups: usize,
+    names: Vec<(String, usize)>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Group(Option<usize>, Box<Node>),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Debug)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl Class {
+    fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(low, high)| low <= c && c <= high) != self.negated
+    }
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('a', 'z'), ('_', '_')];
+const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\r')];
+
+type Spans = Vec<Option<(usize, usize)>>;
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            position: 0,
+            groups: 1,
+            names: Vec::new(),
+        };
+        let root = parser.alternation()?;
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("unmatched `)`"));
+        }
+        Ok(Self {
+            pattern: pattern.to_string(),
+            root,
+            groups: parser.groups,
+            names: parser.names,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.captures(text).is_some()
+    }
+
+    /// The groups of the leftmost match, the whole match first, then the groups in the
+    /// order of their opening parenthesis. A group not taking part in the match is `None`.
+    pub fn captures(&self, text: &str) -> Option<Vec<Option<String>>> {
+        let input: Vec<char> = text.chars().collect();
+        let spans = (0..=input.len()).find_map(|start| {
+            let mut spans = vec![None; self.groups];
+            let matched =
+                self.match_node(&self.root, &input, start, &mut spans, &mut |end, spans| {
+                    spans[0] = Some((start, end));
+                    true
+                });
+            matched.then_some(spans)
+        })?;
+        Some(
+            spans
+                .into_iter()
+                .map(|span| span.map(|(start, end)| input[start..end].iter().collect()))
+                .collect(),
+        )
+    }
+
+    /// The index of a group, checking that the pattern has it.
+    pub fn group_index(&self, group: &CaptureGroup) -> Result<usize, RegexError> {
+        match group {
+            CaptureGroup::Index(index) if *index < self.groups => Ok(*index),
+            CaptureGroup::Index(index) => Err(RegexError::UnknownGroup(index.to_string())),
+            CaptureGroup::Name(name) => self
+                .names
+                .iter()
+                .find(|(group, _)| group == name)
+                .map(|(_, index)| *index)
+                .ok_or_else(|| RegexError::UnknownGroup(name.clone())),
+        }
+    }
+
+    // Matches `node` at `position`, then hands over to the continuation `k` matching the
+    // rest, backtracking into `node` while `k` fails.
+    fn match_node(
+        &self,
+        node: &Node,
+        input: &[char],
+        position: usize,
+        spans: &mut Spans,
+        k: &mut dyn FnMut(usize, &mut Spans) -> bool,
+    ) -> bool {
+        match node {
+            Node::Empty => k(position, spans),
+            Node::Char(c) => input.get(position) == Some(c) && k(position + 1, spans),
+            Node::Any => input.get(position).is_some_and(|&c| c != '\n') && k(position + 1, spans),
+            Node::Class(class) => {
+                input.get(position).is_some_and(|&c| class.contains(c)) && k(position + 1, spans)
+            }
+            Node::Start => position == 0 && k(position, spans),
+            Node::End => position == input.len() && k(position, spans),
+            Node::Group(None, node) => self.match_node(node, input, position, spans, k),
+            Node::Group(Some(index), node) => {
+                let index = *index;
+                self.match_node(node, input, position, spans, &mut |end, spans| {
+                    let previous = spans[index].replace((position, end));
+                    k(end, spans) || {
+                        spans[index] = previous;
+                        false
+                    }
+                })
+            }
+            Node::Concat(nodes) => self.match_sequence(nodes, input, position, spans, k),
+            Node::Alternation(branches) => branches
+                .iter()
+                .any(|branch| self.match_node(branch, input, position, spans, &mut *k)),
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => self.match_repeat((node, *min, *max, *greedy), 0, input, position, spans, k),
+        }
+    }
+
+    fn match_sequence(
+        &self,
+        nodes: &[Node],
+        input: &[char],
+        position: usize,
+        spans: &mut Spans,
+        k: &mut dyn FnMut(usize, &mut Spans) -> bool,
+    ) -> bool {
+        match nodes.split_first() {
+            None => k(position, spans),
+            Some((first, rest)) => {
+                self.match_node(first, input, position, spans, &mut |next, spans| {
+                    self.match_sequence(rest, input, next, spans, k)
+                })
+            }
+        }
+    }
+
+    // Matches the repeated node once more, or not, in the order its greediness prefers.
+    // A repetition matching empty doesn't count beyond the minimum, so it can't loop.
+    fn match_repeat(
+        &self,
+        repeat: (&Node, usize, Option<usize>, bool),
+        count: usize,
+        input: &[char],
+        position: usize,
+        spans: &mut Spans,
+        k: &mut dyn FnMut(usize, &mut Spans) -> bool,
+    ) -> bool {
+        let (node, min, max, greedy) = repeat;
+        let once_more = |spans: &mut Spans, k: &mut dyn FnMut(usize, &mut Spans) -> bool| {
+            max.is_none_or(|max| count < max)
+                && self.match_node(node, input, position, spans, &mut |next, spans| {
+                    (next != position || count < min)
+                        && self.match_repeat(repeat, count + 1, input, next, spans, k)
+                })
+        };
+        if count < min {
+            return once_more(spans, k);
+        }
+        if greedy && once_more(spans, &mut *k) {
+            return true;
+        }
+        k(position, spans) || (!greedy && once_more(spans, k))
+    }
+}
+
+impl Specification<String> for Regex {
+    fn is_satisfied_by(&self, candidate: &String) -> bool {
+        self.is_match(candidate)
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    groups: usize,
+    names: Vec<(String, usize)>,
+}
+
+impl Parser {
+    fn alternation(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.sequence()?];
+        while self.eat('|') {
+            branches.push(self.sequence()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Node::Alternation(branches)
+        })
+    }
+
+    fn sequence(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.remove(0),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Node, RegexError> {
+        let c = self.next().unwrap_or_default();
+        Ok(match c {
+            '(' => self.group()?,
+            '[' => Node::Class(self.class()?),
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '\\' => self.escape()?,
+            '*' | '+' | '?' | '{' => {
+                self.position -= 1;
+                return Err(self.error("nothing to repeat"));
+            }
+            c => Node::Char(c),
+        })
+    }
+
+    fn group(&mut self) -> Result<Node, RegexError> {
+        let index = if self.eat('?') {
+            if self.eat(':') {
+                None
+            } else if self.eat('<') || (self.eat('P') && self.eat('<')) {
+                let name = self.name()?;
+                self.names.push((name, self.groups));
+                Some(self.next_group())
+            } else {
+                return Err(self.error("unsupported group"));
+            }
+        } else {
+            Some(self.next_group())
+        };
+        let node = self.alternation()?;
+        if !self.eat(')') {
+            return Err(self.error("unclosed group"));
+        }
+        Ok(Node::Group(index, Box::new(node)))
+    }
+
+    fn name(&mut self) -> Result<String, RegexError> {
+        let mut name = String::new();
+        while let Some(c) = self.next() {
+            match c {
+                '>' if !name.is_empty() => return Ok(name),
+                c if c.is_alphanumeric() || c == '_' => name.push(c),
+                _ => break,
+            }
+        }
+        Err(self.error("invalid group name"))
+    }
+
+    fn next_group(&mut self) -> usize {
+        self.groups += 1;
+        self.groups - 1
+    }
+
+    fn class(&mut self) -> Result<Class, RegexError> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let low = match self.next() {
+                None => return Err(self.error("unclosed class")),
+                Some(']') if !first => return Ok(Class { ranges, negated }),
+                Some('\\') => match self.escape()? {
+                    Node::Char(c) => c,
+                    Node::Class(Class {
+                        ranges: escaped,
+                        negated: false,
+                    }) => {
+                        ranges.extend(escaped);
+                        first = false;
+                        continue;
+                    }
+                    _ => return Err(self.error("unsupported escape in a class")),
+                },
+                Some(c) => c,
+            };
+            first = false;
+            let is_range =
+                self.peek() == Some('-') && self.chars.get(self.position + 1) != Some(&']');
+            if !is_range {
+                ranges.push((low, low));
+                continue;
+            }
+            self.position += 1;
+            let high = match self.next() {
+                Some('\\') => match self.escape()? {
+                    Node::Char(c) => c,
+                    _ => return Err(self.error("invalid range")),
+                },
+                Some(c) => c,
+                None => return Err(self.error("unclosed class")),
+            };
+            if high < low {
+                return Err(self.error("invalid range"));
+            }
+            ranges.push((low, high));
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, RegexError> {
+        let class = |ranges: &[(char, char)], negated| {
+            Node::Class(Class {
+                ranges: ranges.to_vec(),
+                negated,
+            })
+        };
+        Ok(match self.next() {
+            Some('d') => class(DIGIT, false),
+            Some('D') => class(DIGIT, true),
+            Some('w') => class(WORD, false),
+            Some('W') => class(WORD, true),
+            Some('s') => class(SPACE, false),
+            Some('S') => class(SPACE, true),
+            Some('n') => Node::Char('\n'),
+            Some('t') => Node::Char('\t'),
+            Some('r') => Node::Char('\r'),
+            Some(c) if !c.is_alphanumeric() => Node::Char(c),
+            Some(_) => {
+                self.position -= 1;
+                return Err(self.error("unsupported escape"));
+            }
+            None => return Err(self.error("trailing `\\`")),
+        })
+    }
+
+    fn quantified(&mut self, node: Node) -> Result<Node, RegexError> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                let start = self.position;
+                self.position += 1;
+                let bounds = self.bounds();
+                if bounds.is_none() {
+                    self.position = start;
+                    return Err(self.error("invalid repetition"));
+                }
+                self.position -= 1;
+                bounds.unwrap_or_default()
+            }
+            _ => return Ok(node),
+        };
+        self.position += 1;
+        if matches!(node, Node::Start | Node::End) {
+            return Err(self.error("nothing to repeat"));
+        }
+        let greedy = !self.eat('?');
+        Ok(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    // The `n}`, `n,}` or `n,m}` of a counted repetition, up to and including the `}`.
+    fn bounds(&mut self) -> Option<(usize, Option<usize>)> {
+        let min = self.number()?;
+        let max = if self.eat(',') {
+            match self.peek() {
+                Some('}') => None,
+                _ => Some(self.number()?),
+            }
+        } else {
+            Some(min)
+        };
+        (self.eat('}') && max.is_none_or(|max| min <= max)).then_some((min, max))
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.chars[start..self.position]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn error(&self, message: &str) -> RegexError {
+        RegexError::Syntax {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// A capture group of a [`Regex`], by its index, 0 being the whole match, or by its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureGroup {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for CaptureGroup {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<&str> for CaptureGroup {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+/// Satisfied by strings matching `pattern` whose capture `group` satisfies `inner`.
+/// A string the pattern doesn't match, or matches without the group taking part, is rejected.
+/// Fails if the pattern is malformed or doesn't have the group.
+pub fn regex_capture<S>(
+    pattern: &str,
+    group: impl Into<CaptureGroup>,
+    inner: S,
+) -> Result<RegexCapture<S>, RegexError> {
+    let regex = Regex::new(pattern)?;
+    let group = regex.group_index(&group.into())?;
+    Ok(RegexCapture {
+        regex,
+        group,
+        inner,
+    })
+}
+
+#[derive(Debug)]
+pub struct RegexCapture<S> {
+    regex: Regex,
+    group: usize,
+    inner: S,
+}
+
+impl<S: Specification<String>> Specification<String> for RegexCapture<S> {
+    fn is_satisfied_by(&self, candidate: &String) -> bool {
+        self.regex
+            .captures(candidate)
+            .and_then(|mut groups| groups[self.group].take())
+            .is_some_and(|captured| self.inner.is_satisfied_by(&captured))
+    }
+    fn eq_by_debug(&self) -> bool {
+        self.inner.eq_by_debug()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Is(fn(&String) -> bool);
+
+    impl Specification<String> for Is {
+        fn is_satisfied_by(&self, candidate: &String) -> bool {
+            (self.0)(candidate)
+        }
+    }
+
+    fn month() -> Is {
+        Is(|month| {
+            month
+                .parse::<u32>()
+                .is_ok_and(|month| (1..=12).contains(&month))
+        })
+    }
+
+    fn captures(pattern: &str, text: &str) -> Option<Vec<Option<String>>> {
+        Regex::new(pattern).unwrap().captures(text)
+    }
+
+    fn group(text: &str) -> Option<String> {
+        Some(text.to_string())
+    }
+
+    #[test]
+    fn test_regex_capture_named() {
+        let specification = regex_capture(
+            r"^(?P<year>\d{4})-(?P<month>\d{2})-\d{2}$",
+            "month",
+            month(),
+        )
+        .unwrap();
+
+        assert!(specification.is_satisfied_by(&"2024-06-30".to_string()));
+        assert!(!specification.is_satisfied_by(&"2024-13-01".to_string()));
+    }
+
+    #[test]
+    fn test_regex_capture_indexed() {
+        let specification = regex_capture(r"v(\d+)\.(\d+)", 2, Is(|minor| minor == "4")).unwrap();
+
+        assert!(specification.is_satisfied_by(&"release v1.4".to_string()));
+        assert!(!specification.is_satisfied_by(&"release v1.5".to_string()));
+    }
+
+    #[test]
+    fn test_regex_capture_no_match() {
+        let accept = || Is(|_| true);
+        let specification = regex_capture(r"^\d{4}-(\d{2})$", 1, accept()).unwrap();
+
+        assert!(!specification.is_satisfied_by(&"June 2024".to_string()));
+        assert!(specification.is_satisfied_by(&"2024-06".to_string()));
+
+        let optional = regex_capture(r"^a(b)?$", 1, accept()).unwrap();
+        assert!(!optional.is_satisfied_by(&"a".to_string()));
+        assert!(optional.is_satisfied_by(&"ab".to_string()));
+    }
+
+    #[test]
+    fn test_regex_capture_unknown_group() {
+        assert_eq!(
+            regex_capture(r"(\d+)", "day", Is(|_| true)).unwrap_err(),
+            RegexError::UnknownGroup("day".to_string())
+        );
+        assert_eq!(
+            regex_capture(r"(\d+)", 2, Is(|_| true)).unwrap_err(),
+            RegexError::UnknownGroup("2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_captures() {
+        assert_eq!(
+            captures(r"(a+)(b*?)(b)", "xaabbb"),
+            Some(vec![group("aab"), group("aa"), group(""), group("b")])
+        );
+        assert_eq!(
+            captures(r"(?:ab)+|(c)", "ababc"),
+            Some(vec![group("abab"), None])
+        );
+        assert_eq!(
+            captures(r"^(a|ab)(c|bcd)$", "abcd"),
+            Some(vec![group("abcd"), group("a"), group("bcd")])
+        );
+        assert_eq!(captures(r"[^\d\s]{2,3}", "1 ab2"), Some(vec![group("ab")]));
+        assert_eq!(captures(r"[a-c-]+\.", "x-ab."), Some(vec![group("-ab.")]));
+        assert!(Regex::new(r"(a*)*$").unwrap().is_match("b"));
+        assert_eq!(captures(r"^\w+$", "no spaces"), None);
+    }
+
+    #[test]
+    fn test_regex_specification() {
+        let code = Regex::new(r"^[A-Z]{3}-\d{3}$").unwrap();
+
+        assert!(code.is_satisfied_by(&"ABC-123".to_string()));
+        assert!(!code.is_satisfied_by(&"AB-123".to_string()));
+        assert_eq!(code.as_str(), r"^[A-Z]{3}-\d{3}$");
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let error = |pattern: &str| match Regex::new(pattern).unwrap_err() {
+            RegexError::Syntax { position, message } => (position, message),
+            error => panic!("unexpected {:?}", error),
+        };
+
+        assert_eq!(error("(ab"), (3, "unclosed group".to_string()));
+        assert_eq!(error("ab)"), (2, "unmatched `)`".to_string()));
+        assert_eq!(error("*a"), (0, "nothing to repeat".to_string()));
+        assert_eq!(error("a{2,1}"), (1, "invalid repetition".to_string()));
+        assert_eq!(error("[z-a]"), (4, "invalid range".to_string()));
+        assert_eq!(error(r"\q"), (1, "unsupported escape".to_string()));
+        assert_eq!(error("[ab"), (3, "unclosed class".to_string()));
+    }
+}
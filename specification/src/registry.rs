@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{Specification, SpecificationCompositions};
+
+/// Known leaf specifications by their tag, used to rebuild compositions from text.
+///
+/// A leaf is tagged by its [`Specification::name`], so leaves that differ only in their
+/// parameters need to override `name` to be told apart.
+#[derive(Debug)]
+pub struct Registry<T: std::fmt::Debug> {
+    leaves: HashMap<String, Arc<dyn Specification<T>>>,
+}
+
+impl<T: std::fmt::Debug> Registry<T> {
+    pub fn new() -> Self {
+        Self {
+            leaves: HashMap::new(),
+        }
+    }
+
+    pub fn register(mut self, specification: impl Specification<T> + 'static) -> Self {
+        self.leaves
+            .insert(specification.name(), Arc::new(specification));
+        self
+    }
+
+    pub fn get(&self, tag: &str) -> Option<SpecificationCompositions<T>> {
+        self.leaves
+            .get(tag)
+            .map(|leaf| SpecificationCompositions::Specification(leaf.clone()))
+    }
+}
+
+impl<T: std::fmt::Debug> Default for Registry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,136 @@
+// Leaves validating the order of the items within a sequence, like a salary history.
+
+use std::fmt::Debug;
+
+use crate::Specification;
+
+/// Satisfied by sequences where no item is less than the one before it,
+/// equal neighbours are allowed. Empty and single-item sequences are sorted.
+pub fn is_sorted_ascending() -> Sorted {
+    Sorted { descending: false }
+}
+
+/// Satisfied by sequences where no item is greater than the one before it,
+/// equal neighbours are allowed. Empty and single-item sequences are sorted.
+pub fn is_sorted_descending() -> Sorted {
+    Sorted { descending: true }
+}
+
+/// Satisfied by sequences whose every window of `size` consecutive items satisfies `inner`,
+/// for rules like "never three declining salaries in a row". Sequences shorter than
+/// the window have none, so they are satisfied.
+///
+/// # Panics
+///
+/// If `size` is zero.
+pub fn window_all<S>(size: usize, inner: S) -> WindowAll<S> {
+    assert!(size > 0, "window size must be greater than zero");
+    WindowAll { size, inner }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sorted {
+    descending: bool,
+}
+
+impl<V: Debug + PartialOrd> Specification<Vec<V>> for Sorted {
+    fn is_satisfied_by(&self, candidate: &Vec<V>) -> bool {
+        candidate.windows(2).all(|pair| {
+            if self.descending {
+                pair[0] >= pair[1]
+            } else {
+                pair[0] <= pair[1]
+            }
+        })
+    }
+    fn eq_by_debug(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct WindowAll<S> {
+    size: usize,
+    inner: S,
+}
+
+impl<V: Debug, S: for<'a> Specification<&'a [V]>> Specification<Vec<V>> for WindowAll<S> {
+    fn is_satisfied_by(&self, candidate: &Vec<V>) -> bool {
+        candidate
+            .windows(self.size)
+            .all(|window| self.inner.is_satisfied_by(&window))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_sorted_ascending() {
+        let specification = is_sorted_ascending();
+
+        assert!(specification.is_satisfied_by(&vec![90_000, 100_000, 100_000, 130_000]));
+        assert!(!specification.is_satisfied_by(&vec![90_000, 130_000, 100_000]));
+        assert!(!specification.is_satisfied_by(&vec![130_000, 90_000]));
+    }
+
+    #[test]
+    fn test_is_sorted_descending() {
+        let specification = is_sorted_descending();
+
+        assert!(specification.is_satisfied_by(&vec![3.5, 2.0, 2.0, -1.0]));
+        assert!(!specification.is_satisfied_by(&vec![3.5, 4.0, 1.0]));
+        assert!(!specification.is_satisfied_by(&vec![1.0, 2.0]));
+    }
+
+    #[test]
+    fn test_is_sorted_trivial_sequences() {
+        for specification in [is_sorted_ascending(), is_sorted_descending()] {
+            assert!(specification.is_satisfied_by(&Vec::<i64>::new()));
+            assert!(specification.is_satisfied_by(&vec![42]));
+        }
+    }
+
+    #[derive(Debug)]
+    struct NotDeclining;
+
+    impl Specification<&[i64]> for NotDeclining {
+        fn is_satisfied_by(&self, candidate: &&[i64]) -> bool {
+            candidate.windows(2).any(|pair| pair[0] <= pair[1])
+        }
+    }
+
+    #[test]
+    fn test_window_all() {
+        let specification = window_all(3, NotDeclining);
+
+        assert!(specification.is_satisfied_by(&vec![3, 2, 4, 3, 5]));
+        assert!(!specification.is_satisfied_by(&vec![5, 4, 3, 4, 5]));
+        assert!(!specification.is_satisfied_by(&vec![5, 4, 3, 2, 1]));
+    }
+
+    #[test]
+    fn test_window_all_shorter_than_the_window() {
+        let specification = window_all(3, NotDeclining);
+
+        assert!(specification.is_satisfied_by(&vec![2, 1]));
+        assert!(specification.is_satisfied_by(&Vec::new()));
+        assert!(!specification.is_satisfied_by(&vec![3, 2, 1]));
+    }
+
+    #[test]
+    #[should_panic(expected = "window size")]
+    fn test_window_all_empty_window() {
+        window_all(0, NotDeclining);
+    }
+
+    #[test]
+    fn test_is_sorted_composition() {
+        let specification = is_sorted_ascending().and(is_sorted_descending().invert());
+
+        assert!(specification.is_satisfied_by(&vec![1, 2, 3]));
+        assert!(!specification.is_satisfied_by(&vec![2, 2, 2]));
+        assert!(!specification.is_satisfied_by(&vec![3, 1, 2]));
+    }
+}
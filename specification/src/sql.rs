@@ -0,0 +1,111 @@
+// Translating compositions into SQL conditions, to filter in the database instead of in memory.
+
+use crate::SpecificationCompositions;
+
+/// A leaf specification that can be expressed as an SQL condition.
+///
+/// Implementors also need to override [`crate::Specification::as_to_sql`] to return
+/// themselves, that's how a composition finds the translation behind its leaves.
+pub trait ToSql {
+    fn to_sql(&self) -> String;
+}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// The composition as the condition of a `WHERE` clause, `None` if any of its leaves
+    /// can't be translated. `Xor` and the threshold combinators have no SQL counterpart either.
+    pub fn to_sql(&self) -> Option<String> {
+        match self {
+            Self::Specification(f) => f.as_to_sql().map(ToSql::to_sql),
+            Self::And(specifications) => Self::join_sql(specifications, " AND ", "TRUE"),
+            Self::Or(specifications) => Self::join_sql(specifications, " OR ", "FALSE"),
+            Self::Invert(specification) => Some(format!("NOT {}", specification.to_sql()?)),
+            Self::True => Some("TRUE".to_string()),
+            Self::False => Some("FALSE".to_string()),
+            Self::Xor(..)
+            | Self::WeightedAtLeast(..)
+            | Self::MinSatisfied(..)
+            | Self::MaxSatisfied(..) => None,
+        }
+    }
+
+    fn join_sql(specifications: &[Self], separator: &str, empty: &str) -> Option<String> {
+        if specifications.is_empty() {
+            return Some(empty.to_string());
+        }
+        let conditions = specifications
+            .iter()
+            .map(Self::to_sql)
+            .collect::<Option<Vec<String>>>()?;
+        Some(format!("({})", conditions.join(separator)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+    use crate::Specification;
+
+    #[derive(Debug)]
+    struct AgeAbove(i32);
+
+    impl Specification<i32> for AgeAbove {
+        fn is_satisfied_by(&self, candidate: &i32) -> bool {
+            candidate > &self.0
+        }
+        fn as_to_sql(&self) -> Option<&dyn ToSql> {
+            Some(self)
+        }
+    }
+
+    impl ToSql for AgeAbove {
+        fn to_sql(&self) -> String {
+            format!("age > {}", self.0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct AgeBelow(i32);
+
+    impl Specification<i32> for AgeBelow {
+        fn is_satisfied_by(&self, candidate: &i32) -> bool {
+            candidate < &self.0
+        }
+        fn as_to_sql(&self) -> Option<&dyn ToSql> {
+            Some(self)
+        }
+    }
+
+    impl ToSql for AgeBelow {
+        fn to_sql(&self) -> String {
+            format!("age < {}", self.0)
+        }
+    }
+
+    #[test]
+    fn test_to_sql() {
+        let specification = SpecificationCompositions::And(vec![
+            AgeAbove(18).and(AgeBelow(65)).or(AgeAbove(90).invert()),
+            SpecificationCompositions::True,
+        ]);
+
+        assert_eq!(
+            specification.to_sql().unwrap(),
+            "(((age > 18 AND age < 65) OR NOT age > 90) AND TRUE)"
+        );
+    }
+
+    #[test]
+    fn test_to_sql_unsupported_leaf() {
+        let specification = AgeAbove(18).and(GreaterThan { value: 5 });
+
+        assert_eq!(specification.to_sql(), None);
+    }
+
+    #[test]
+    fn test_to_sql_xor() {
+        let specification = AgeAbove(18).xor(AgeBelow(65));
+
+        assert_eq!(specification.to_sql(), None);
+    }
+}
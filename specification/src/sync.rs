@@ -0,0 +1,187 @@
+// A composition whose leaves are all `Send + Sync`, so it can be shared across threads.
+
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::{Specification, SpecificationCompositions};
+
+pub trait SyncSpecification<T: std::fmt::Debug>: Specification<T> + Send + Sync {
+    fn sync(self) -> SyncCompositions<T>
+    where
+        Self: 'static + Sized,
+    {
+        SyncCompositions::Specification(Arc::new(self))
+    }
+}
+
+impl<T: std::fmt::Debug, S: Specification<T> + Send + Sync + ?Sized> SyncSpecification<T> for S {}
+
+/// The thread safe counterpart of [`SpecificationCompositions`].
+#[derive(Debug)]
+pub enum SyncCompositions<T: std::fmt::Debug> {
+    Specification(Arc<dyn SyncSpecification<T>>),
+    And(Vec<SyncCompositions<T>>),
+    Or(Vec<SyncCompositions<T>>),
+    Xor(Vec<SyncCompositions<T>>),
+    Invert(Box<SyncCompositions<T>>),
+    True,
+    False,
+}
+
+impl<T: std::fmt::Debug> Clone for SyncCompositions<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Specification(f) => Self::Specification(f.clone()),
+            Self::And(specifications) => Self::And(specifications.clone()),
+            Self::Or(specifications) => Self::Or(specifications.clone()),
+            Self::Xor(specifications) => Self::Xor(specifications.clone()),
+            Self::Invert(specification) => Self::Invert(specification.clone()),
+            Self::True => Self::True,
+            Self::False => Self::False,
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> Specification<T> for SyncCompositions<T> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        match self {
+            Self::Specification(f) => f.is_satisfied_by(candidate),
+            Self::And(specifications) => specifications
+                .iter()
+                .all(|specification| specification.is_satisfied_by(candidate)),
+            Self::Or(specifications) => specifications
+                .iter()
+                .any(|specification| specification.is_satisfied_by(candidate)),
+            Self::Invert(specification) => !specification.is_satisfied_by(candidate),
+            Self::Xor(specifications) => {
+                specifications
+                    .iter()
+                    .filter(|specification| specification.is_satisfied_by(candidate))
+                    .count()
+                    % 2
+                    == 1
+            }
+            Self::True => true,
+            Self::False => false,
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> SyncCompositions<T> {
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::And(mut specifications), Self::And(others)) => {
+                specifications.extend(others);
+                Self::And(specifications)
+            }
+            (Self::And(mut specifications), other) => {
+                specifications.push(other);
+                Self::And(specifications)
+            }
+            (specification, other) => Self::And(vec![specification, other]),
+        }
+    }
+    pub fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Or(mut specifications), Self::Or(others)) => {
+                specifications.extend(others);
+                Self::Or(specifications)
+            }
+            (Self::Or(mut specifications), other) => {
+                specifications.push(other);
+                Self::Or(specifications)
+            }
+            (specification, other) => Self::Or(vec![specification, other]),
+        }
+    }
+    pub fn xor(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Xor(mut specifications), Self::Xor(others)) => {
+                specifications.extend(others);
+                Self::Xor(specifications)
+            }
+            (Self::Xor(mut specifications), other) => {
+                specifications.push(other);
+                Self::Xor(specifications)
+            }
+            (specification, other) => Self::Xor(vec![specification, other]),
+        }
+    }
+    pub fn invert(self) -> Self {
+        Self::Invert(Box::new(self))
+    }
+}
+
+// Every thread safe composition is a composition too, so the analysis tools work on it as well.
+impl<T: std::fmt::Debug> From<SyncCompositions<T>> for SpecificationCompositions<T> {
+    fn from(specification: SyncCompositions<T>) -> Self {
+        match specification {
+            SyncCompositions::Specification(f) => Self::Specification(f),
+            SyncCompositions::And(specifications) => {
+                Self::And(specifications.into_iter().map(Self::from).collect())
+            }
+            SyncCompositions::Or(specifications) => {
+                Self::Or(specifications.into_iter().map(Self::from).collect())
+            }
+            SyncCompositions::Xor(specifications) => {
+                Self::Xor(specifications.into_iter().map(Self::from).collect())
+            }
+            SyncCompositions::Invert(specification) => {
+                Self::Invert(Box::new(Self::from(*specification)))
+            }
+            SyncCompositions::True => Self::True,
+            SyncCompositions::False => Self::False,
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> Display for SyncCompositions<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", SpecificationCompositions::from(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+    use std::thread;
+
+    #[test]
+    fn test_shared_across_threads() {
+        let specification = Arc::new(
+            GreaterThan { value: 5 }
+                .sync()
+                .and(LessThan { value: 10 }.sync())
+                .or(Zero {}.sync()),
+        );
+
+        let handles: Vec<_> = (0..12)
+            .map(|candidate| {
+                let specification = specification.clone();
+                thread::spawn(move || (candidate, specification.is_satisfied_by(&candidate)))
+            })
+            .collect();
+
+        for handle in handles {
+            let (candidate, satisfied) = handle.join().unwrap();
+            assert_eq!(satisfied, candidate == 0 || (6..10).contains(&candidate));
+        }
+    }
+
+    #[test]
+    fn test_into_compositions() {
+        let specification = GreaterThan { value: 5 }
+            .sync()
+            .and(Zero {}.sync().invert())
+            .xor(SyncCompositions::True);
+
+        assert_eq!(
+            specification.to_string(),
+            "((GreaterThan { value: 5 } and not Zero) xor true)"
+        );
+        let specification = SpecificationCompositions::from(specification);
+        assert!(specification.is_satisfied_by(&0));
+        assert!(!specification.is_satisfied_by(&6));
+    }
+}
@@ -0,0 +1,120 @@
+// Hard requirements and soft preferences, evaluated together but reported apart.
+
+use crate::{Specification, SpecificationCompositions};
+
+/// A candidate has to meet `required`, and is ranked by how many of the `preferred` criteria
+/// it meets. The criteria are the direct children of `preferred` when it is an `And` or an
+/// `Or`, and `preferred` itself otherwise, so a `not X` preference is one criterion, met when
+/// `X` isn't.
+#[derive(Debug, Clone)]
+pub struct TieredSpecification<T: std::fmt::Debug> {
+    pub required: SpecificationCompositions<T>,
+    pub preferred: SpecificationCompositions<T>,
+}
+
+/// How a candidate did, see [`TieredSpecification::evaluate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TierResult {
+    pub required_met: bool,
+    pub preferred_satisfied: usize,
+    pub preferred_total: usize,
+}
+
+impl<T: std::fmt::Debug> TieredSpecification<T> {
+    pub fn new(
+        required: SpecificationCompositions<T>,
+        preferred: SpecificationCompositions<T>,
+    ) -> Self {
+        Self {
+            required,
+            preferred,
+        }
+    }
+
+    /// Evaluates both tiers, the preferences are counted even when the requirements aren't met.
+    pub fn evaluate(&self, candidate: &T) -> TierResult {
+        let preferences = match &self.preferred {
+            SpecificationCompositions::And(children) | SpecificationCompositions::Or(children) => {
+                children.iter().collect()
+            }
+            preferred => vec![preferred],
+        };
+        TierResult {
+            required_met: self.required.is_satisfied_by(candidate),
+            preferred_satisfied: preferences
+                .iter()
+                .filter(|preference| preference.is_satisfied_by(candidate))
+                .count(),
+            preferred_total: preferences.len(),
+        }
+    }
+}
+
+// As a plain specification only the requirements count.
+impl<T: std::fmt::Debug> Specification<T> for TieredSpecification<T> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.required.is_satisfied_by(candidate)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_evaluate() {
+        let tiered = TieredSpecification::new(
+            GreaterThan { value: 0 }.composite(),
+            LessThan { value: 10 }
+                .and(LessThan { value: 100 })
+                .and(GreaterThan { value: 50 }),
+        );
+
+        assert_eq!(
+            tiered.evaluate(&70),
+            TierResult {
+                required_met: true,
+                preferred_satisfied: 2,
+                preferred_total: 3
+            }
+        );
+        assert_eq!(tiered.evaluate(&-1).preferred_satisfied, 2);
+        assert!(!tiered.evaluate(&-1).required_met);
+        assert!(tiered.is_satisfied_by(&70));
+        assert!(!tiered.is_satisfied_by(&-1));
+    }
+
+    #[test]
+    fn test_evaluate_single_preference() {
+        let tiered = TieredSpecification::new(SpecificationCompositions::True, Zero {}.composite());
+
+        assert_eq!(tiered.evaluate(&0).preferred_satisfied, 1);
+        assert_eq!(tiered.evaluate(&1).preferred_satisfied, 0);
+        assert_eq!(tiered.evaluate(&1).preferred_total, 1);
+    }
+
+    #[test]
+    fn test_evaluate_negated_preference() {
+        let tiered = TieredSpecification::new(SpecificationCompositions::True, Zero {}.invert());
+
+        assert_eq!(tiered.evaluate(&1).preferred_satisfied, 1);
+        assert_eq!(tiered.evaluate(&0).preferred_satisfied, 0);
+        assert_eq!(tiered.evaluate(&0).preferred_total, 1);
+
+        let tiered = TieredSpecification::new(
+            SpecificationCompositions::True,
+            Zero {}.invert().and(GreaterThan { value: 5 }.xor(Zero {})),
+        );
+
+        assert_eq!(
+            tiered.evaluate(&0),
+            TierResult {
+                required_met: true,
+                preferred_satisfied: 1,
+                preferred_total: 2
+            }
+        );
+        assert_eq!(tiered.evaluate(&6).preferred_satisfied, 2);
+    }
+}
@@ -0,0 +1,246 @@
+// A wrapper reporting every evaluation of a specification, and a log of how a whole
+// composition was evaluated, for debugging.
+
+use std::fmt::{Debug, Formatter};
+
+use crate::analysis::NodeId;
+use crate::{Specification, SpecificationCompositions};
+
+type Callback<T> = Box<dyn Fn(&T, bool)>;
+
+/// Calls `callback` with the candidate and the result after every evaluation of
+/// `specification`, otherwise it behaves exactly like the wrapped specification.
+pub struct Traced<T, S> {
+    specification: S,
+    callback: Callback<T>,
+}
+
+impl<T, S> Traced<T, S> {
+    pub fn new(specification: S, callback: impl Fn(&T, bool) + 'static) -> Self {
+        Self {
+            specification,
+            callback: Box::new(callback),
+        }
+    }
+}
+
+impl<T, S: Debug> Debug for Traced<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Traced")
+            .field("specification", &self.specification)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug, S: Specification<T>> Specification<T> for Traced<T, S> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        let satisfied = self.specification.is_satisfied_by(candidate);
+        (self.callback)(candidate, satisfied);
+        satisfied
+    }
+    fn name(&self) -> String {
+        self.specification.name()
+    }
+    fn cost(&self) -> u32 {
+        self.specification.cost()
+    }
+    fn importance(&self) -> u32 {
+        self.specification.importance()
+    }
+}
+
+/// A step of an evaluation, see [`SpecificationCompositions::evaluation_log`].
+///
+/// Nodes are identified as by [`SpecificationCompositions::assign_ids`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalEvent {
+    /// A combinator started evaluating its children.
+    Enter { node: NodeId },
+    /// A leaf, or a `True` or `False`, was evaluated.
+    LeafResult { name: String, result: bool },
+    /// A combinator was decided before evaluating all of its children, the rest were skipped.
+    ShortCircuit { node: NodeId },
+    /// A combinator was decided.
+    Exit { node: NodeId, result: bool },
+}
+
+impl<T: Debug> SpecificationCompositions<T> {
+    /// Evaluates the composition, recording every step in order,
+    /// including where `And` and `Or` short-circuited.
+    pub fn evaluation_log(&self, candidate: &T) -> Vec<EvalEvent> {
+        let mut events = Vec::new();
+        self.evaluate_logging(candidate, &mut 0, &mut events);
+        events
+    }
+
+    fn evaluate_logging(
+        &self,
+        candidate: &T,
+        next: &mut usize,
+        events: &mut Vec<EvalEvent>,
+    ) -> bool {
+        let node = NodeId(*next);
+        *next += 1;
+        if let Self::Specification(..) | Self::True | Self::False = self {
+            let result = self.is_satisfied_by(candidate);
+            events.push(EvalEvent::LeafResult {
+                name: self.name(),
+                result,
+            });
+            return result;
+        }
+        events.push(EvalEvent::Enter { node });
+        let children = self.children();
+        let mut results = Vec::new();
+        for (position, child) in children.iter().enumerate() {
+            let result = child.evaluate_logging(candidate, next, events);
+            results.push(result);
+            let decided = match self {
+                Self::And(..) => !result,
+                Self::Or(..) => result,
+                _ => false,
+            };
+            if decided && position + 1 < children.len() {
+                events.push(EvalEvent::ShortCircuit { node });
+                // The skipped nodes keep their ids.
+                *next += children[position + 1..]
+                    .iter()
+                    .map(|child| child.node_count())
+                    .sum::<usize>();
+                break;
+            }
+        }
+        let satisfied = results.iter().filter(|result| **result).count();
+        let result = match self {
+            Self::And(..) => satisfied == results.len(),
+            Self::Or(..) => satisfied > 0,
+            Self::Xor(..) => satisfied % 2 == 1,
+            Self::Invert(..) => satisfied == 0,
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let weight: f64 = specifications
+                    .iter()
+                    .zip(&results)
+                    .filter(|(_, result)| **result)
+                    .map(|((weight, _), _)| weight)
+                    .sum();
+                weight >= *threshold
+            }
+            Self::MinSatisfied(count, _) => satisfied >= *count,
+            Self::MaxSatisfied(count, _) => satisfied <= *count,
+            Self::Specification(..) | Self::True | Self::False => unreachable!(),
+        };
+        events.push(EvalEvent::Exit { node, result });
+        result
+    }
+
+    fn node_count(&self) -> usize {
+        1 + self
+            .children()
+            .into_iter()
+            .map(|child| child.node_count())
+            .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn test_traced() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let recorded = calls.clone();
+        let traced = Traced::new(
+            GreaterThan { value: 5 },
+            move |candidate: &i32, satisfied| recorded.borrow_mut().push((*candidate, satisfied)),
+        );
+
+        assert!(traced.is_satisfied_by(&6));
+        assert!(!traced.is_satisfied_by(&3));
+
+        assert_eq!(*calls.borrow(), vec![(6, true), (3, false)]);
+        assert_eq!(traced.name(), "GreaterThan");
+    }
+
+    #[test]
+    fn test_traced_in_composition() {
+        let calls = Rc::new(RefCell::new(0));
+        let counted = calls.clone();
+        let specification = Zero {}.or(Traced::new(LessThan { value: 10 }, move |_: &i32, _| {
+            *counted.borrow_mut() += 1
+        }));
+
+        assert!(specification.is_satisfied_by(&0));
+        assert!(specification.is_satisfied_by(&5));
+
+        assert_eq!(*calls.borrow(), 1);
+    }
+
+    #[test]
+    fn test_evaluation_log_short_circuit() {
+        let specification = GreaterThan { value: 5 }.and(LessThan { value: 10 });
+
+        assert_eq!(
+            specification.evaluation_log(&3),
+            vec![
+                EvalEvent::Enter { node: NodeId(0) },
+                EvalEvent::LeafResult {
+                    name: "GreaterThan".to_string(),
+                    result: false
+                },
+                EvalEvent::ShortCircuit { node: NodeId(0) },
+                EvalEvent::Exit {
+                    node: NodeId(0),
+                    result: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluation_log_nested() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 })
+            .or(Zero {}.invert());
+
+        let events = specification.evaluation_log(&3);
+
+        assert_eq!(
+            events,
+            vec![
+                EvalEvent::Enter { node: NodeId(0) },
+                EvalEvent::Enter { node: NodeId(1) },
+                EvalEvent::LeafResult {
+                    name: "GreaterThan".to_string(),
+                    result: false
+                },
+                EvalEvent::ShortCircuit { node: NodeId(1) },
+                EvalEvent::Exit {
+                    node: NodeId(1),
+                    result: false
+                },
+                EvalEvent::Enter { node: NodeId(4) },
+                EvalEvent::LeafResult {
+                    name: "Zero".to_string(),
+                    result: false
+                },
+                EvalEvent::Exit {
+                    node: NodeId(4),
+                    result: true
+                },
+                EvalEvent::Exit {
+                    node: NodeId(0),
+                    result: true
+                },
+            ]
+        );
+        assert_eq!(
+            specification.assign_ids().find(NodeId(4)).unwrap().name,
+            "not"
+        );
+    }
+}
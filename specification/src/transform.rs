@@ -0,0 +1,869 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::{Specification, SpecificationCompositions};
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Reorders the children of `And` and `Or` nodes by ascending [`Specification::cost`],
+    /// so short-circuiting skips the expensive checks whenever a cheap one decides.
+    ///
+    /// Without knowing how likely a child holds, cheap children are simply moved to the front,
+    /// for `Or` as well. The order of equally costly children is kept, and `Xor` and the
+    /// threshold combinators are left alone as they always evaluate every child.
+    /// This only preserves semantics for pure predicates.
+    pub fn optimize(self) -> Self {
+        match self {
+            Self::And(specifications) => Self::And(Self::optimize_children(specifications)),
+            Self::Or(specifications) => Self::Or(Self::optimize_children(specifications)),
+            Self::Xor(specifications) => {
+                Self::Xor(specifications.into_iter().map(Self::optimize).collect())
+            }
+            Self::Invert(specification) => Self::Invert(Box::new(specification.optimize())),
+            Self::WeightedAtLeast(threshold, specifications) => Self::WeightedAtLeast(
+                threshold,
+                specifications
+                    .into_iter()
+                    .map(|(weight, specification)| (weight, specification.optimize()))
+                    .collect(),
+            ),
+            Self::MinSatisfied(count, specifications) => Self::MinSatisfied(
+                count,
+                specifications.into_iter().map(Self::optimize).collect(),
+            ),
+            Self::MaxSatisfied(count, specifications) => Self::MaxSatisfied(
+                count,
+                specifications.into_iter().map(Self::optimize).collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Negation normal form: pushes every `Invert` down to the leaves using De Morgan's laws,
+    /// collapsing double negations. A negated `Xor` negates its first child instead.
+    pub fn to_nnf(self) -> Self {
+        self.nnf(false)
+    }
+
+    fn nnf(self, negated: bool) -> Self {
+        match (self, negated) {
+            (Self::Invert(specification), negated) => specification.nnf(!negated),
+            (Self::And(specifications), false) => Self::And(Self::nnf_all(specifications, false)),
+            (Self::And(specifications), true) => Self::Or(Self::nnf_all(specifications, true)),
+            (Self::Or(specifications), false) => Self::Or(Self::nnf_all(specifications, false)),
+            (Self::Or(specifications), true) => Self::And(Self::nnf_all(specifications, true)),
+            (Self::Xor(specifications), negated) => {
+                if specifications.is_empty() && negated {
+                    return Self::True;
+                }
+                Self::Xor(
+                    specifications
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, specification)| specification.nnf(negated && i == 0))
+                        .collect(),
+                )
+            }
+            (Self::True, true) => Self::False,
+            (Self::False, true) => Self::True,
+            (Self::Specification(f), true) => Self::Invert(Box::new(Self::Specification(f))),
+            // There is no simple dual of a weighted threshold, the negation stays above it.
+            (Self::WeightedAtLeast(threshold, specifications), negated) => {
+                let specification = Self::WeightedAtLeast(
+                    threshold,
+                    specifications
+                        .into_iter()
+                        .map(|(weight, specification)| (weight, specification.nnf(false)))
+                        .collect(),
+                );
+                if negated {
+                    Self::Invert(Box::new(specification))
+                } else {
+                    specification
+                }
+            }
+            // Fewer than `count` held means at most `count - 1` did, and the other way around.
+            (Self::MinSatisfied(0, _), true) => Self::False,
+            (Self::MinSatisfied(count, specifications), true) => {
+                Self::MaxSatisfied(count - 1, Self::nnf_all(specifications, false))
+            }
+            (Self::MaxSatisfied(count, specifications), true) => {
+                Self::MinSatisfied(count + 1, Self::nnf_all(specifications, false))
+            }
+            (Self::MinSatisfied(count, specifications), false) => {
+                Self::MinSatisfied(count, Self::nnf_all(specifications, false))
+            }
+            (Self::MaxSatisfied(count, specifications), false) => {
+                Self::MaxSatisfied(count, Self::nnf_all(specifications, false))
+            }
+            (specification, false) => specification,
+        }
+    }
+
+    fn nnf_all(specifications: Vec<Self>, negated: bool) -> Vec<Self> {
+        specifications
+            .into_iter()
+            .map(|specification| specification.nnf(negated))
+            .collect()
+    }
+
+    /// Sorts the children of every combinator by their textual form, so logically equal
+    /// trees built in a different order end up identical, e.g. to be used as cache keys.
+    pub fn canonicalize(self) -> Self {
+        match self {
+            Self::And(specifications) => Self::And(Self::canonicalize_children(specifications)),
+            Self::Or(specifications) => Self::Or(Self::canonicalize_children(specifications)),
+            Self::Xor(specifications) => Self::Xor(Self::canonicalize_children(specifications)),
+            Self::Invert(specification) => Self::Invert(Box::new(specification.canonicalize())),
+            Self::WeightedAtLeast(threshold, specifications) => {
+                let mut specifications: Vec<(f64, Self)> = specifications
+                    .into_iter()
+                    .map(|(weight, specification)| (weight, specification.canonicalize()))
+                    .collect();
+                specifications.sort_by_cached_key(|(weight, specification)| {
+                    format!("{} * {}", weight, specification)
+                });
+                Self::WeightedAtLeast(threshold, specifications)
+            }
+            Self::MinSatisfied(count, specifications) => {
+                Self::MinSatisfied(count, Self::canonicalize_children(specifications))
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                Self::MaxSatisfied(count, Self::canonicalize_children(specifications))
+            }
+            other => other,
+        }
+    }
+
+    fn canonicalize_children(specifications: Vec<Self>) -> Vec<Self> {
+        let mut specifications: Vec<Self> =
+            specifications.into_iter().map(Self::canonicalize).collect();
+        specifications.sort_by_cached_key(ToString::to_string);
+        specifications
+    }
+
+    /// Makes equal subtrees, see `==`, share a single copy, so trees repeating the same
+    /// subtrees keep only one of each. Equal leaves share one `Arc`, and a combinator subtree
+    /// occurring more than once is kept once, behind an `Arc`, as a leaf standing in for it
+    /// at every occurrence. Such a leaf evaluates, is named and prints like the subtree,
+    /// but the transformations and analyses walking the tree no longer look inside it.
+    pub fn intern(self) -> Self
+    where
+        T: 'static,
+    {
+        let mut occurrences: HashMap<&Self, usize> = HashMap::new();
+        self.count_subtrees(&mut occurrences);
+        let repeated: HashSet<Self> = occurrences
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(subtree, _)| subtree.clone())
+            .collect();
+        self.intern_with(&repeated, &mut HashMap::new())
+    }
+
+    // Counts the combinator subtrees, the ones inside a subtree seen before only once.
+    fn count_subtrees<'a>(&'a self, occurrences: &mut HashMap<&'a Self, usize>) {
+        if matches!(self, Self::Specification(..) | Self::True | Self::False) {
+            return;
+        }
+        let count = occurrences.entry(self).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            for child in self.children() {
+                child.count_subtrees(occurrences);
+            }
+        }
+    }
+
+    fn intern_with(
+        self,
+        repeated: &HashSet<Self>,
+        shared: &mut HashMap<Self, Arc<dyn Specification<T>>>,
+    ) -> Self
+    where
+        T: 'static,
+    {
+        if let Self::Specification(f) = self {
+            let shared = shared
+                .entry(Self::Specification(f.clone()))
+                .or_insert(f)
+                .clone();
+            return Self::Specification(shared);
+        }
+        if !repeated.contains(&self) {
+            return self.map_children(&mut |child| child.intern_with(repeated, shared));
+        }
+        if let Some(f) = shared.get(&self) {
+            return Self::Specification(f.clone());
+        }
+        let subtree = self.clone();
+        let interned = self.map_children(&mut |child| child.intern_with(repeated, shared));
+        let f: Arc<dyn Specification<T>> = Arc::new(Shared(interned));
+        shared.insert(subtree, f.clone());
+        Self::Specification(f)
+    }
+
+    /// Replaces every leaf selected by `predicate` with the composition `replacement` builds
+    /// for it, e.g. to swap a deprecated leaf. The rest of the tree is kept as it is.
+    pub fn replace_leaves_where<P, R>(self, predicate: P, replacement: R) -> Self
+    where
+        P: Fn(&Arc<dyn Specification<T>>) -> bool,
+        R: Fn(&Arc<dyn Specification<T>>) -> Self,
+    {
+        self.map_leaves(&mut |f| {
+            if predicate(&f) {
+                replacement(&f)
+            } else {
+                Self::Specification(f)
+            }
+        })
+    }
+
+    // Rebuilds the tree with every leaf replaced by what `f` makes of it.
+    fn map_leaves(self, f: &mut impl FnMut(Arc<dyn Specification<T>>) -> Self) -> Self {
+        match self {
+            Self::Specification(leaf) => f(leaf),
+            Self::And(specifications) => Self::And(Self::map_all_leaves(specifications, f)),
+            Self::Or(specifications) => Self::Or(Self::map_all_leaves(specifications, f)),
+            Self::Xor(specifications) => Self::Xor(Self::map_all_leaves(specifications, f)),
+            Self::Invert(specification) => Self::Invert(Box::new(specification.map_leaves(f))),
+            Self::WeightedAtLeast(threshold, specifications) => Self::WeightedAtLeast(
+                threshold,
+                specifications
+                    .into_iter()
+                    .map(|(weight, specification)| (weight, specification.map_leaves(f)))
+                    .collect(),
+            ),
+            Self::MinSatisfied(count, specifications) => {
+                Self::MinSatisfied(count, Self::map_all_leaves(specifications, f))
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                Self::MaxSatisfied(count, Self::map_all_leaves(specifications, f))
+            }
+            other => other,
+        }
+    }
+
+    fn map_all_leaves(
+        specifications: Vec<Self>,
+        f: &mut impl FnMut(Arc<dyn Specification<T>>) -> Self,
+    ) -> Vec<Self> {
+        specifications
+            .into_iter()
+            .map(|specification| specification.map_leaves(f))
+            .collect()
+    }
+
+    /// Merges nested `And`, `Or` and `Xor` nodes into their parent of the same kind,
+    /// as they are associative: `a and (b and c)` becomes `a and b and c`.
+    pub fn flatten(self) -> Self {
+        match self.map_children(&mut Self::flatten) {
+            Self::And(specifications) => Self::And(Self::flatten_children(specifications, |s| {
+                matches!(s, Self::And(..))
+            })),
+            Self::Or(specifications) => Self::Or(Self::flatten_children(specifications, |s| {
+                matches!(s, Self::Or(..))
+            })),
+            Self::Xor(specifications) => Self::Xor(Self::flatten_children(specifications, |s| {
+                matches!(s, Self::Xor(..))
+            })),
+            other => other,
+        }
+    }
+
+    fn flatten_children(specifications: Vec<Self>, same_kind: impl Fn(&Self) -> bool) -> Vec<Self> {
+        let mut flattened = Vec::new();
+        for specification in specifications {
+            if same_kind(&specification) {
+                flattened.extend(specification.children().into_iter().cloned());
+            } else {
+                flattened.push(specification);
+            }
+        }
+        flattened
+    }
+
+    /// Removes the `True` and `False` children that don't decide anything, and resolves the
+    /// nodes they do decide, like an `And` with a `False` child. Combinators left with
+    /// a single child are replaced by it, empty ones by their identity element.
+    pub fn prune_constants(self) -> Self {
+        match self.map_children(&mut Self::prune_constants) {
+            Self::And(mut specifications) => {
+                if specifications.contains(&Self::False) {
+                    return Self::False;
+                }
+                specifications.retain(|specification| specification != &Self::True);
+                Self::unwrap_single(specifications, Self::And, Self::True)
+            }
+            Self::Or(mut specifications) => {
+                if specifications.contains(&Self::True) {
+                    return Self::True;
+                }
+                specifications.retain(|specification| specification != &Self::False);
+                Self::unwrap_single(specifications, Self::Or, Self::False)
+            }
+            // Each `True` flips the parity of the rest.
+            Self::Xor(mut specifications) => {
+                let trues = specifications
+                    .iter()
+                    .filter(|specification| *specification == &Self::True)
+                    .count();
+                specifications
+                    .retain(|specification| !matches!(specification, Self::True | Self::False));
+                let specification = Self::unwrap_single(specifications, Self::Xor, Self::False);
+                if trues % 2 == 0 {
+                    specification
+                } else {
+                    Self::Invert(Box::new(specification)).prune_constants()
+                }
+            }
+            Self::Invert(specification) => match *specification {
+                Self::True => Self::False,
+                Self::False => Self::True,
+                specification => Self::Invert(Box::new(specification)),
+            },
+            other => other,
+        }
+    }
+
+    fn unwrap_single(
+        mut specifications: Vec<Self>,
+        combinator: impl FnOnce(Vec<Self>) -> Self,
+        identity: Self,
+    ) -> Self {
+        match specifications.len() {
+            0 => identity,
+            1 => specifications.remove(0),
+            _ => combinator(specifications),
+        }
+    }
+
+    /// Removes repeated children: `And` and `Or` keep the first of equal children,
+    /// in `Xor` equal children cancel out in pairs. The threshold combinators count
+    /// their children, so those are kept as they are.
+    ///
+    /// Children are equal as in `==`: leaves holding closures are only repeated when they
+    /// are the very same leaf, two closures printing alike are both kept.
+    pub fn dedup(self) -> Self {
+        match self.map_children(&mut Self::dedup) {
+            Self::And(specifications) => Self::And(Self::dedup_children(specifications)),
+            Self::Or(specifications) => Self::Or(Self::dedup_children(specifications)),
+            Self::Xor(specifications) => {
+                let mut remaining: Vec<Self> = Vec::new();
+                for specification in specifications {
+                    match remaining.iter().position(|other| other == &specification) {
+                        Some(position) => {
+                            remaining.remove(position);
+                        }
+                        None => remaining.push(specification),
+                    }
+                }
+                Self::Xor(remaining)
+            }
+            other => other,
+        }
+    }
+
+    fn dedup_children(specifications: Vec<Self>) -> Vec<Self> {
+        let mut unique: Vec<Self> = Vec::new();
+        for specification in specifications {
+            if !unique.contains(&specification) {
+                unique.push(specification);
+            }
+        }
+        unique
+    }
+
+    /// Tidies the tree by applying [`Self::flatten`], [`Self::prune_constants`] and
+    /// [`Self::dedup`], in this order, until none of them changes anything anymore.
+    ///
+    /// Flattening first lets pruning and deduplication see all the siblings at once, and
+    /// repeating catches what they uncover, e.g. an `And` left with a single `And` child.
+    /// As the result is a fixed point, normalizing it again gives the same tree.
+    pub fn normalize(self) -> Self {
+        let mut specification = self;
+        loop {
+            let normalized = specification.clone().flatten().prune_constants().dedup();
+            if normalized == specification {
+                return normalized;
+            }
+            specification = normalized;
+        }
+    }
+
+    /// Like [`Self::normalize`], but borrows the tree when it is already normalized,
+    /// so nothing is cloned or allocated in the common case of a tidy tree.
+    pub fn simplify_cow(&self) -> Cow<'_, Self> {
+        if self.is_normalized() {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.clone().normalize())
+        }
+    }
+
+    // Whether `normalize` would leave the tree as it is.
+    fn is_normalized(&self) -> bool {
+        let tidy = match self {
+            Self::And(specifications) | Self::Or(specifications) | Self::Xor(specifications) => {
+                specifications.len() > 1
+                    && specifications.iter().enumerate().all(|(i, specification)| {
+                        !matches!(specification, Self::True | Self::False)
+                            && std::mem::discriminant(specification) != std::mem::discriminant(self)
+                            && !specifications[..i].contains(specification)
+                    })
+            }
+            Self::Invert(specification) => !matches!(**specification, Self::True | Self::False),
+            _ => true,
+        };
+        tidy && self.children().into_iter().all(Self::is_normalized)
+    }
+
+    // Rebuilds the node with `f` applied to each of its direct children.
+    fn map_children(self, f: &mut impl FnMut(Self) -> Self) -> Self {
+        match self {
+            Self::And(specifications) => {
+                Self::And(specifications.into_iter().map(&mut *f).collect())
+            }
+            Self::Or(specifications) => Self::Or(specifications.into_iter().map(&mut *f).collect()),
+            Self::Xor(specifications) => {
+                Self::Xor(specifications.into_iter().map(&mut *f).collect())
+            }
+            Self::Invert(specification) => Self::Invert(Box::new(f(*specification))),
+            Self::WeightedAtLeast(threshold, specifications) => Self::WeightedAtLeast(
+                threshold,
+                specifications
+                    .into_iter()
+                    .map(|(weight, specification)| (weight, f(specification)))
+                    .collect(),
+            ),
+            Self::MinSatisfied(count, specifications) => {
+                Self::MinSatisfied(count, specifications.into_iter().map(&mut *f).collect())
+            }
+            Self::MaxSatisfied(count, specifications) => {
+                Self::MaxSatisfied(count, specifications.into_iter().map(&mut *f).collect())
+            }
+            other => other,
+        }
+    }
+
+    fn optimize_children(specifications: Vec<Self>) -> Vec<Self> {
+        let mut specifications: Vec<Self> =
+            specifications.into_iter().map(Self::optimize).collect();
+        specifications.sort_by_key(Specification::cost);
+        specifications
+    }
+}
+
+// A subtree occurring more than once, as the leaf its occurrences share, see `intern`.
+// It prints like the subtree, so an interned tree still displays the same.
+struct Shared<T: std::fmt::Debug>(SpecificationCompositions<T>);
+
+impl<T: std::fmt::Debug> Debug for Shared<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<T: std::fmt::Debug> Specification<T> for Shared<T> {
+    fn is_satisfied_by(&self, candidate: &T) -> bool {
+        self.0.is_satisfied_by(candidate)
+    }
+
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    fn cost(&self) -> u32 {
+        self.0.cost()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[derive(Debug)]
+    struct Costly {
+        value: i32,
+        cost: u32,
+    }
+
+    impl Specification<i32> for Costly {
+        fn is_satisfied_by(&self, candidate: &i32) -> bool {
+            candidate > &self.value
+        }
+        fn cost(&self) -> u32 {
+            self.cost
+        }
+    }
+
+    fn costs(specification: &SpecificationCompositions<i32>) -> Vec<u32> {
+        match specification {
+            SpecificationCompositions::And(specifications)
+            | SpecificationCompositions::Or(specifications) => {
+                specifications.iter().map(Specification::cost).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn only_leaves_inverted(specification: &SpecificationCompositions<i32>) -> bool {
+        match specification {
+            SpecificationCompositions::Invert(inverted) => {
+                matches!(**inverted, SpecificationCompositions::Specification(..))
+            }
+            specification => specification
+                .children()
+                .into_iter()
+                .all(only_leaves_inverted),
+        }
+    }
+
+    #[test]
+    fn test_to_nnf() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 }.or(Zero {}.invert()))
+            .invert()
+            .or(GreaterThan { value: 20 }
+                .xor(LessThan { value: 30 })
+                .invert())
+            .or(SpecificationCompositions::True.invert().invert().invert());
+
+        let nnf = specification.clone().to_nnf();
+
+        assert!(only_leaves_inverted(&nnf));
+        assert_eq!(
+            nnf.to_string(),
+            "((not GreaterThan { value: 5 } or (not LessThan { value: 10 } and Zero)) or (not GreaterThan { value: 20 } xor LessThan { value: 30 }) or false)"
+        );
+        for candidate in -10..40 {
+            assert_eq!(
+                nnf.is_satisfied_by(&candidate),
+                specification.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_nnf_double_negation() {
+        let specification = Zero {}.invert().invert();
+
+        assert!(matches!(
+            specification.to_nnf(),
+            SpecificationCompositions::Specification(..)
+        ));
+    }
+
+    #[test]
+    fn test_to_nnf_min_max_satisfied() {
+        let children = || {
+            vec![
+                GreaterThan { value: 5 }.composite(),
+                LessThan { value: 10 }.invert(),
+                Zero {}.composite(),
+            ]
+        };
+        let specification = SpecificationCompositions::MinSatisfied(2, children())
+            .invert()
+            .or(SpecificationCompositions::MaxSatisfied(1, children()).invert())
+            .or(SpecificationCompositions::MinSatisfied(0, children()).invert());
+
+        let nnf = specification.clone().to_nnf();
+
+        assert!(only_leaves_inverted(&nnf));
+        for candidate in -10..20 {
+            assert_eq!(
+                nnf.is_satisfied_by(&candidate),
+                specification.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let a = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 }.or(Zero {}.invert()))
+            .and(Zero {});
+        let b = Zero {}
+            .and(Zero {}.invert().or(LessThan { value: 10 }))
+            .and(GreaterThan { value: 5 });
+        assert_ne!(a, b);
+
+        let (a, b) = (a.canonicalize(), b.canonicalize());
+
+        assert_eq!(a, b);
+        assert_eq!(
+            a.to_string(),
+            "((LessThan { value: 10 } or not Zero) and GreaterThan { value: 5 } and Zero)"
+        );
+    }
+
+    #[test]
+    fn test_intern_closure_leaves() {
+        use crate::comparison::{fields_compare, CmpOp};
+
+        let specification = fields_compare(|c: &i32| *c, |_: &i32| 5, CmpOp::Gt)
+            .and(fields_compare(|c: &i32| *c, |_: &i32| 10, CmpOp::Lt));
+
+        let interned = specification.intern();
+
+        assert!(interned.is_satisfied_by(&7));
+        assert!(!interned.is_satisfied_by(&3));
+        assert!(!interned.is_satisfied_by(&12));
+    }
+
+    #[test]
+    fn test_intern() {
+        let branch = || GreaterThan { value: 5 }.and(Zero {}.invert());
+        let specification = branch().or(branch()).or(LessThan { value: 5 });
+
+        let interned = specification.clone().intern();
+
+        assert_eq!(interned.to_string(), specification.to_string());
+        for candidate in -10..10 {
+            assert_eq!(
+                interned.is_satisfied_by(&candidate),
+                specification.is_satisfied_by(&candidate)
+            );
+        }
+        let branches = interned.children();
+        assert_eq!(branches.len(), 3);
+        match (branches[0], branches[1]) {
+            (
+                SpecificationCompositions::Specification(a),
+                SpecificationCompositions::Specification(b),
+            ) => {
+                assert!(Arc::ptr_eq(a, b));
+                assert_eq!(a.name(), "and");
+            }
+            _ => panic!("expected shared branches"),
+        }
+    }
+
+    #[test]
+    fn test_intern_leaves() {
+        let specification = GreaterThan { value: 5 }
+            .and(Zero {})
+            .or(GreaterThan { value: 5 }.and(LessThan { value: 10 }));
+
+        let interned = specification.clone().intern();
+
+        assert_eq!(interned, specification);
+        let branches = interned.children();
+        match (&branches[0].children()[0], &branches[1].children()[0]) {
+            (
+                SpecificationCompositions::Specification(a),
+                SpecificationCompositions::Specification(b),
+            ) => {
+                assert!(Arc::ptr_eq(a, b))
+            }
+            _ => panic!("expected leaves"),
+        }
+    }
+
+    #[test]
+    fn test_replace_leaves_where() {
+        let specification = GreaterThan { value: 5 }
+            .and(LessThan { value: 10 }.or(Zero {}.invert()))
+            .xor(Zero {});
+
+        let replaced = specification.replace_leaves_where(
+            |leaf| leaf.name() == "Zero",
+            |_| LessThan { value: 1 }.and(GreaterThan { value: -1 }),
+        );
+
+        assert_eq!(
+            replaced.to_string(),
+            "((GreaterThan { value: 5 } and (LessThan { value: 10 } or not (LessThan { value: 1 } and GreaterThan { value: -1 }))) xor (LessThan { value: 1 } and GreaterThan { value: -1 }))"
+        );
+    }
+
+    #[test]
+    fn test_replace_leaves_where_keeps_other_leaves() {
+        let specification = GreaterThan { value: 5 }.and(Zero {});
+        let original = match &specification {
+            SpecificationCompositions::And(specifications) => match &specifications[0] {
+                SpecificationCompositions::Specification(leaf) => leaf.clone(),
+                _ => panic!("expected a leaf"),
+            },
+            _ => panic!("expected an And"),
+        };
+
+        let replaced = specification.replace_leaves_where(
+            |leaf| leaf.name() == "Zero",
+            |_| SpecificationCompositions::True,
+        );
+
+        match &replaced.children()[0] {
+            SpecificationCompositions::Specification(leaf) => assert!(Arc::ptr_eq(leaf, &original)),
+            _ => panic!("expected a leaf"),
+        }
+        assert_eq!(replaced.children()[1], &SpecificationCompositions::True);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let specification = SpecificationCompositions::And(vec![
+            Zero {}.composite(),
+            GreaterThan { value: 5 }.or(LessThan { value: 10 }.or(Zero {})),
+            SpecificationCompositions::And(vec![LessThan { value: 3 }.composite()]),
+        ]);
+
+        assert_eq!(
+            specification.flatten().to_string(),
+            "(Zero and (GreaterThan { value: 5 } or LessThan { value: 10 } or Zero) and LessThan { value: 3 })"
+        );
+    }
+
+    #[test]
+    fn test_prune_constants() {
+        use SpecificationCompositions::{False, True};
+
+        assert_eq!(Zero {}.and(True).prune_constants(), Zero {}.composite());
+        assert_eq!(Zero {}.and(False).prune_constants(), False);
+        assert_eq!(Zero {}.or(True).prune_constants(), True);
+        assert_eq!(Zero {}.xor(True).prune_constants(), Zero {}.invert());
+        assert_eq!(True::<i32>.invert().prune_constants(), False);
+        assert_eq!(
+            SpecificationCompositions::<i32>::And(vec![]).prune_constants(),
+            True
+        );
+    }
+
+    #[test]
+    fn test_dedup_closure_leaves() {
+        use crate::comparison::{fields_compare, CmpOp};
+
+        type Triple = (i32, i32, i32);
+        let specification = fields_compare(|c: &Triple| c.0, |c: &Triple| c.1, CmpOp::Gt).and(
+            fields_compare(|c: &Triple| c.1, |c: &Triple| c.2, CmpOp::Gt),
+        );
+
+        for transformed in [specification.clone().dedup(), specification.normalize()] {
+            assert_eq!(transformed.children().len(), 2);
+            assert!(transformed.is_satisfied_by(&(3, 2, 1)));
+            assert!(!transformed.is_satisfied_by(&(3, 2, 5)));
+        }
+    }
+
+    #[test]
+    fn test_dedup() {
+        let specification = Zero {}
+            .and(GreaterThan { value: 5 })
+            .and(Zero {})
+            .or(LessThan { value: 3 }
+                .xor(Zero {})
+                .xor(LessThan { value: 3 }));
+
+        assert_eq!(
+            specification.dedup().to_string(),
+            "((Zero and GreaterThan { value: 5 }) or (Zero))"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        use SpecificationCompositions::{And, False, Or, True};
+
+        let specification = And(vec![
+            Or(vec![
+                False,
+                Zero {}.composite(),
+                Or(vec![
+                    Zero {}.composite(),
+                    GreaterThan { value: 5 }.composite(),
+                ]),
+            ]),
+            True,
+            And(vec![
+                LessThan { value: 10 }.composite(),
+                True.invert().invert(),
+            ]),
+            LessThan { value: 10 }.composite(),
+        ]);
+
+        let normalized = specification.clone().normalize();
+
+        assert_eq!(
+            normalized.to_string(),
+            "((Zero or GreaterThan { value: 5 }) and LessThan { value: 10 })"
+        );
+        assert_eq!(normalized.clone().normalize(), normalized);
+        for candidate in -5..15 {
+            assert_eq!(
+                normalized.is_satisfied_by(&candidate),
+                specification.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_simplify_cow_borrowed() {
+        let specification = GreaterThan { value: 5 }
+            .or(Zero {}.invert())
+            .and(LessThan { value: 10 });
+
+        assert!(matches!(specification.simplify_cow(), Cow::Borrowed(..)));
+        assert!(matches!(
+            Zero {}.composite().simplify_cow(),
+            Cow::Borrowed(..)
+        ));
+    }
+
+    #[test]
+    fn test_simplify_cow_owned() {
+        let specifications = [
+            SpecificationCompositions::And(vec![
+                Zero {}.composite(),
+                SpecificationCompositions::True,
+            ]),
+            Zero {}.or(Zero {}),
+            SpecificationCompositions::And(vec![
+                Zero {}.composite(),
+                Zero {}.and(LessThan { value: 3 }),
+            ]),
+            SpecificationCompositions::True.invert(),
+        ];
+
+        for specification in specifications {
+            let simplified = specification.simplify_cow();
+            assert!(matches!(simplified, Cow::Owned(..)));
+            assert_eq!(simplified.into_owned(), specification.clone().normalize());
+        }
+    }
+
+    #[test]
+    fn test_optimize_and() {
+        let specification = Costly { value: 1, cost: 10 }
+            .and(Costly { value: 2, cost: 1 })
+            .and(Costly { value: 3, cost: 5 });
+
+        let optimized = specification.clone().optimize();
+
+        assert_eq!(costs(&optimized), vec![1, 5, 10]);
+        for candidate in 0..5 {
+            assert_eq!(
+                specification.is_satisfied_by(&candidate),
+                optimized.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_optimize_nested() {
+        let expensive = Costly { value: 1, cost: 7 }.or(Costly { value: 2, cost: 3 });
+        let specification = expensive.and(Costly { value: 3, cost: 4 });
+
+        let optimized = specification.optimize();
+
+        assert_eq!(costs(&optimized), vec![4, 10]);
+        match optimized {
+            SpecificationCompositions::And(specifications) => {
+                assert_eq!(costs(&specifications[1]), vec![3, 7]);
+            }
+            _ => panic!("expected an And"),
+        }
+    }
+}
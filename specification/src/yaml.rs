@@ -0,0 +1,433 @@
+// A hand written YAML representation of the composition tree.
+// It only reads and writes the small subset of YAML it produces:
+//
+// and:
+//   - leaf: GreaterThan5
+//   - not:
+//       leaf: Zero
+//   - true
+
+use std::fmt::{Display, Formatter};
+
+use crate::registry::Registry;
+use crate::SpecificationCompositions;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YamlError {
+    UnknownLeaf(String),
+    Syntax { line: usize, message: String },
+}
+
+impl Display for YamlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownLeaf(tag) => write!(f, "unknown leaf `{}`", tag),
+            Self::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+impl<T: std::fmt::Debug> SpecificationCompositions<T> {
+    /// Renders the tree as YAML, leaves are written by their [`crate::Specification::name`].
+    pub fn to_yaml(&self) -> String {
+        let mut output = String::new();
+        self.write_yaml(0, &mut output);
+        output
+    }
+
+    /// Rebuilds a tree written by [`SpecificationCompositions::to_yaml`],
+    /// looking up the leaves in the `registry`.
+    pub fn from_yaml(s: &str, registry: &Registry<T>) -> Result<Self, YamlError> {
+        let mut parser = Parser {
+            tokens: tokenize(s),
+            position: 0,
+            registry,
+        };
+        let specification = parser.node(0)?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(specification),
+            Some(token) => Err(YamlError::Syntax {
+                line: token.line,
+                message: "unexpected content after the specification".to_string(),
+            }),
+        }
+    }
+
+    fn write_yaml(&self, indent: usize, output: &mut String) {
+        match self {
+            Self::Specification(f) => output.push_str(&format!("leaf: {}\n", f.name())),
+            Self::And(specifications) => {
+                Self::write_yaml_sequence("and", specifications, indent, output)
+            }
+            Self::Or(specifications) => {
+                Self::write_yaml_sequence("or", specifications, indent, output)
+            }
+            Self::Xor(specifications) => {
+                Self::write_yaml_sequence("xor", specifications, indent, output)
+            }
+            Self::Invert(specification) => {
+                output.push_str("not:\n");
+                output.push_str(&" ".repeat(indent + 2));
+                specification.write_yaml(indent + 2, output);
+            }
+            Self::True => output.push_str("true\n"),
+            Self::False => output.push_str("false\n"),
+            Self::WeightedAtLeast(threshold, specifications) => {
+                output.push_str(&format!("weighted_at_least {}:\n", threshold));
+                for (weight, specification) in specifications {
+                    output.push_str(&" ".repeat(indent + 2));
+                    output.push_str(&format!("- weight {}:\n", weight));
+                    output.push_str(&" ".repeat(indent + 6));
+                    specification.write_yaml(indent + 6, output);
+                }
+            }
+            Self::MinSatisfied(count, specifications) => Self::write_yaml_sequence(
+                &format!("min_satisfied {}", count),
+                specifications,
+                indent,
+                output,
+            ),
+            Self::MaxSatisfied(count, specifications) => Self::write_yaml_sequence(
+                &format!("max_satisfied {}", count),
+                specifications,
+                indent,
+                output,
+            ),
+        }
+    }
+
+    fn write_yaml_sequence(key: &str, specifications: &[Self], indent: usize, output: &mut String) {
+        if specifications.is_empty() {
+            output.push_str(&format!("{}: []\n", key));
+            return;
+        }
+        output.push_str(&format!("{}:\n", key));
+        for specification in specifications {
+            output.push_str(&" ".repeat(indent + 2));
+            output.push_str("- ");
+            specification.write_yaml(indent + 4, output);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Content<'a> {
+    Dash,
+    Text(&'a str),
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+    line: usize,
+    indent: usize,
+    content: Content<'a>,
+}
+
+// Splits the lines into indented tokens, a `- ` prefix becomes a separate `Dash` token
+// with the rest of the line indented after it.
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (number, line) in s.lines().enumerate() {
+        let content = line.split(" #").next().unwrap_or(line).trim_end();
+        let mut rest = content.trim_start();
+        if rest.is_empty() || rest.starts_with('#') {
+            continue;
+        }
+        let mut indent = content.len() - rest.len();
+        while let Some(stripped) = rest.strip_prefix('-') {
+            if !(stripped.is_empty() || stripped.starts_with(' ')) {
+                break;
+            }
+            tokens.push(Token {
+                line: number + 1,
+                indent,
+                content: Content::Dash,
+            });
+            let trimmed = stripped.trim_start();
+            indent += rest.len() - trimmed.len();
+            rest = trimmed;
+        }
+        if !rest.is_empty() {
+            tokens.push(Token {
+                line: number + 1,
+                indent,
+                content: Content::Text(rest),
+            });
+        }
+    }
+    tokens
+}
+
+struct Parser<'a, 'r, T: std::fmt::Debug> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    registry: &'r Registry<T>,
+}
+
+impl<T: std::fmt::Debug> Parser<'_, '_, T> {
+    fn node(&mut self, min_indent: usize) -> Result<SpecificationCompositions<T>, YamlError> {
+        let token = match self.tokens.get(self.position) {
+            Some(token) if token.indent >= min_indent => token,
+            Some(token) => {
+                return Err(self.error(token.line, "expected a more indented specification"))
+            }
+            None => return Err(self.error(self.last_line(), "expected a specification")),
+        };
+        let (line, indent) = (token.line, token.indent);
+        let text = match token.content {
+            Content::Text(text) => text,
+            Content::Dash => return Err(self.error(line, "unexpected sequence item")),
+        };
+        self.position += 1;
+
+        let (key, value) = match text.split_once(':') {
+            Some((key, value)) => (key.trim(), Some(value.trim())),
+            None => (text.trim(), None),
+        };
+        match (key, value) {
+            ("true", None) => Ok(SpecificationCompositions::True),
+            ("false", None) => Ok(SpecificationCompositions::False),
+            ("leaf", Some(tag)) if !tag.is_empty() => self
+                .registry
+                .get(tag)
+                .ok_or_else(|| YamlError::UnknownLeaf(tag.to_string())),
+            ("and", Some(value)) => Ok(SpecificationCompositions::And(
+                self.sequence(line, indent, value)?,
+            )),
+            ("or", Some(value)) => Ok(SpecificationCompositions::Or(
+                self.sequence(line, indent, value)?,
+            )),
+            ("xor", Some(value)) => Ok(SpecificationCompositions::Xor(
+                self.sequence(line, indent, value)?,
+            )),
+            ("not", Some("")) => Ok(SpecificationCompositions::Invert(Box::new(
+                self.node(indent + 1)?,
+            ))),
+            ("not", Some("true")) => Ok(SpecificationCompositions::True.invert()),
+            ("not", Some("false")) => Ok(SpecificationCompositions::False.invert()),
+            (key, Some("")) if key.starts_with("weighted_at_least ") => {
+                let threshold = self.number(line, &key["weighted_at_least ".len()..])?;
+                Ok(SpecificationCompositions::WeightedAtLeast(
+                    threshold,
+                    self.weighted_sequence(indent)?,
+                ))
+            }
+            (key, Some(value)) if key.starts_with("min_satisfied ") => {
+                let count = self.count(line, &key["min_satisfied ".len()..])?;
+                Ok(SpecificationCompositions::MinSatisfied(
+                    count,
+                    self.sequence(line, indent, value)?,
+                ))
+            }
+            (key, Some(value)) if key.starts_with("max_satisfied ") => {
+                let count = self.count(line, &key["max_satisfied ".len()..])?;
+                Ok(SpecificationCompositions::MaxSatisfied(
+                    count,
+                    self.sequence(line, indent, value)?,
+                ))
+            }
+            _ => Err(self.error(line, &format!("unexpected `{}`", text))),
+        }
+    }
+
+    fn sequence(
+        &mut self,
+        line: usize,
+        indent: usize,
+        value: &str,
+    ) -> Result<Vec<SpecificationCompositions<T>>, YamlError> {
+        match value {
+            "[]" => return Ok(vec![]),
+            "" => {}
+            _ => return Err(self.error(line, "expected a sequence")),
+        }
+        let mut specifications = Vec::new();
+        let mut item_indent = None;
+        while let Some(token) = self.tokens.get(self.position) {
+            if !matches!(token.content, Content::Dash) || token.indent < indent {
+                break;
+            }
+            match item_indent {
+                None => item_indent = Some(token.indent),
+                Some(item_indent) if item_indent != token.indent => break,
+                Some(_) => {}
+            }
+            let dash_indent = token.indent;
+            self.position += 1;
+            specifications.push(self.node(dash_indent + 1)?);
+        }
+        if specifications.is_empty() {
+            return Err(self.error(line, "expected at least one sequence item"));
+        }
+        Ok(specifications)
+    }
+
+    fn weighted_sequence(
+        &mut self,
+        indent: usize,
+    ) -> Result<Vec<(f64, SpecificationCompositions<T>)>, YamlError> {
+        let mut specifications = Vec::new();
+        while let Some(token) = self.tokens.get(self.position) {
+            if !matches!(token.content, Content::Dash) || token.indent < indent {
+                break;
+            }
+            self.position += 1;
+            let (line, weight_indent, weight) = match self.tokens.get(self.position) {
+                Some(Token {
+                    line,
+                    indent,
+                    content: Content::Text(text),
+                }) => (
+                    *line,
+                    *indent,
+                    text.strip_prefix("weight ")
+                        .and_then(|w| w.strip_suffix(':')),
+                ),
+                _ => (self.last_line(), 0, None),
+            };
+            let weight = match weight {
+                Some(weight) => self.number(line, weight)?,
+                None => return Err(self.error(line, "expected a `weight <number>:` item")),
+            };
+            self.position += 1;
+            specifications.push((weight, self.node(weight_indent + 1)?));
+        }
+        Ok(specifications)
+    }
+
+    fn number(&self, line: usize, text: &str) -> Result<f64, YamlError> {
+        text.trim()
+            .parse()
+            .map_err(|_| self.error(line, &format!("`{}` is not a number", text.trim())))
+    }
+
+    fn count(&self, line: usize, text: &str) -> Result<usize, YamlError> {
+        text.trim()
+            .parse()
+            .map_err(|_| self.error(line, &format!("`{}` is not a count", text.trim())))
+    }
+
+    fn last_line(&self) -> usize {
+        self.tokens.last().map_or(0, |token| token.line)
+    }
+
+    fn error(&self, line: usize, message: &str) -> YamlError {
+        YamlError::Syntax {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::*;
+    use crate::Specification;
+
+    fn registry() -> Registry<i32> {
+        Registry::new()
+            .register(greater_than_5())
+            .register(less_than_10())
+            .register(Zero {})
+    }
+
+    #[test]
+    fn test_to_yaml() {
+        let specification = SpecificationCompositions::And(vec![
+            greater_than_5().and(less_than_10()).or(Zero {}.invert()),
+            SpecificationCompositions::True,
+        ]);
+
+        assert_eq!(
+            specification.to_yaml(),
+            "and:\n  - or:\n      - and:\n          - leaf: GreaterThan5\n          - leaf: LessThan10\n      - not:\n          leaf: Zero\n  - true\n"
+        );
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let specification = greater_than_5()
+            .and(less_than_10())
+            .or(Zero {}.invert().xor(SpecificationCompositions::False))
+            .and(SpecificationCompositions::Or(vec![]));
+
+        let yaml = specification.to_yaml();
+        let parsed = SpecificationCompositions::from_yaml(&yaml, &registry()).unwrap();
+
+        assert_eq!(parsed.to_yaml(), yaml);
+        assert_eq!(parsed.to_string(), specification.to_string());
+        for candidate in -5..15 {
+            assert_eq!(
+                parsed.is_satisfied_by(&candidate),
+                specification.is_satisfied_by(&candidate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_max_satisfied_yaml_round_trip() {
+        let specification = SpecificationCompositions::MinSatisfied(
+            2,
+            vec![
+                greater_than_5().composite(),
+                SpecificationCompositions::MaxSatisfied(
+                    1,
+                    vec![less_than_10().composite(), Zero {}.composite()],
+                ),
+            ],
+        );
+
+        let yaml = specification.to_yaml();
+        assert_eq!(
+            yaml,
+            "min_satisfied 2:\n  - leaf: GreaterThan5\n  - max_satisfied 1:\n      - leaf: LessThan10\n      - leaf: Zero\n"
+        );
+        let parsed = SpecificationCompositions::from_yaml(&yaml, &registry()).unwrap();
+        assert_eq!(parsed.to_yaml(), yaml);
+    }
+
+    #[test]
+    fn test_weighted_yaml_round_trip() {
+        let specification = SpecificationCompositions::WeightedAtLeast(
+            2.5,
+            vec![
+                (2.0, greater_than_5().composite()),
+                (1.5, less_than_10().or(Zero {})),
+            ],
+        );
+
+        let yaml = specification.to_yaml();
+        assert_eq!(
+            yaml,
+            "weighted_at_least 2.5:\n  - weight 2:\n      leaf: GreaterThan5\n  - weight 1.5:\n      or:\n        - leaf: LessThan10\n        - leaf: Zero\n"
+        );
+        let parsed = SpecificationCompositions::from_yaml(&yaml, &registry()).unwrap();
+        assert_eq!(parsed.to_yaml(), yaml);
+    }
+
+    #[test]
+    fn test_from_hand_written_yaml() {
+        let yaml = "# rule edited by hand\nor:\n- leaf: Zero\n- and:\n  - leaf: GreaterThan5\n  - leaf: LessThan10\n";
+
+        let parsed = SpecificationCompositions::from_yaml(yaml, &registry()).unwrap();
+
+        assert!(parsed.is_satisfied_by(&0));
+        assert!(parsed.is_satisfied_by(&7));
+        assert!(!parsed.is_satisfied_by(&3));
+    }
+
+    #[test]
+    fn test_from_yaml_errors() {
+        let unknown = SpecificationCompositions::from_yaml("leaf: Unknown", &registry());
+        assert_eq!(
+            unknown.unwrap_err(),
+            YamlError::UnknownLeaf("Unknown".to_string())
+        );
+
+        let invalid = SpecificationCompositions::from_yaml("and:\n  - maybe\n", &registry());
+        assert!(matches!(invalid, Err(YamlError::Syntax { line: 2, .. })));
+    }
+}